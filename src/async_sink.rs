@@ -0,0 +1,390 @@
+use std::collections::VecDeque;
+use std::io;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+/// What an [`AsyncSink`](struct.AsyncSink.html) does with new messages when
+/// its queue is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait until there is room in the queue.
+    Block,
+    /// Drop the new message.
+    DropNewest,
+    /// Drop the oldest queued message to make room for the new one.
+    DropOldest,
+}
+
+/// A sink that hands messages to a background thread for writing.
+///
+/// Messages are still formatted on the logging thread, but are then pushed to
+/// a bounded queue and written to the wrapped sink by a dedicated writer
+/// thread, so a slow sink doesn't stall the threads doing the logging. When
+/// the queue is full, new messages are handled according to an
+/// [`OverflowPolicy`](enum.OverflowPolicy.html).
+///
+/// Write errors happen on the writer thread, so they are reported by the next
+/// call to `write` or `flush` instead. If the wrapped sink panics, the writer
+/// thread stops and all further writes and flushes fail. Flushing waits until
+/// all queued messages are written and the wrapped sink is flushed. Dropping
+/// the `AsyncSink` writes any queued messages and stops the writer thread.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::{AsyncSink, OverflowPolicy};
+/// use std::io;
+///
+/// # fn main() {
+/// let sink = AsyncSink::new(io::stderr(), 1024, OverflowPolicy::DropOldest);
+/// let stats = sink.stats();
+/// simple_logging::log_to(sink, LevelFilter::Info);
+/// // ...
+/// println!("{} messages dropped", stats.dropped());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncSink {
+    shared: Arc<Shared>,
+    writer: Option<JoinHandle<()>>,
+}
+
+/// Statistics of an [`AsyncSink`](struct.AsyncSink.html), available even after
+/// the sink has been handed to the logger.
+#[derive(Clone, Debug)]
+pub struct AsyncStats {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    // Signalled when there is work for the writer thread
+    work: Condvar,
+    // Signalled when the writer thread makes progress
+    progress: Condvar,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: AtomicUsize,
+}
+
+#[derive(Debug)]
+struct State {
+    queue: VecDeque<Vec<u8>>,
+    // Whether the writer thread is writing a message taken from the queue
+    busy: bool,
+    flush_requested: u64,
+    flushed: u64,
+    shutdown: bool,
+    // Whether the writer thread has stopped
+    dead: bool,
+    error: Option<io::Error>,
+}
+
+impl AsyncSink {
+    /// Wrap `sink`, queueing up to `capacity` messages (at least one) and
+    /// applying `policy` when the queue is full.
+    pub fn new<T: Write + Send + 'static>(
+        sink: T,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> AsyncSink {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                queue: VecDeque::new(),
+                busy: false,
+                flush_requested: 0,
+                flushed: 0,
+                shutdown: false,
+                dead: false,
+                error: None,
+            }),
+            work: Condvar::new(),
+            progress: Condvar::new(),
+            capacity: capacity.max(1),
+            policy,
+            dropped: AtomicUsize::new(0),
+        });
+        let writer = {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name("simple-logging".to_owned())
+                .spawn(move || shared.run(sink))
+                .expect("failed to spawn the log writer thread")
+        };
+
+        AsyncSink {
+            shared,
+            writer: Some(writer),
+        }
+    }
+
+    /// Get a handle to the statistics of this sink.
+    pub fn stats(&self) -> AsyncStats {
+        AsyncStats {
+            shared: self.shared.clone(),
+        }
+    }
+
+    // Return and clear the last error from the writer thread, or fail if the
+    // thread has stopped.
+    fn take_error(state: &mut State) -> io::Result<()> {
+        match state.error.take() {
+            Some(err) => Err(err),
+            None if state.dead => {
+                Err(io::Error::other("the log writer thread has stopped"))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl AsyncStats {
+    /// Number of messages dropped because the queue was full.
+    pub fn dropped(&self) -> usize {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    // The writer thread's main loop.
+    fn run<T: Write>(&self, mut sink: T) {
+        let _stopped = Stopped(self);
+        let mut state = self.lock();
+        loop {
+            if let Some(message) = state.queue.pop_front() {
+                state.busy = true;
+                self.progress.notify_all();
+                drop(state);
+                let result = sink.write_all(&message);
+                state = self.lock();
+                state.busy = false;
+                if let Err(err) = result {
+                    state.error = Some(err);
+                }
+            } else if state.flush_requested > state.flushed {
+                let requested = state.flush_requested;
+                drop(state);
+                let result = sink.flush();
+                state = self.lock();
+                state.flushed = requested;
+                if let Err(err) = result {
+                    state.error = Some(err);
+                }
+                self.progress.notify_all();
+            } else if state.shutdown {
+                let _ = sink.flush();
+                return;
+            } else {
+                self.progress.notify_all();
+                state = self
+                    .work
+                    .wait(state)
+                    .unwrap_or_else(|err| err.into_inner());
+            }
+        }
+    }
+}
+
+// Marks the writer thread as stopped when dropped, including when the sink
+// panics, and wakes up any threads waiting for it.
+struct Stopped<'a>(&'a Shared);
+
+impl<'a> Drop for Stopped<'a> {
+    fn drop(&mut self) {
+        self.0.lock().dead = true;
+        self.0.progress.notify_all();
+    }
+}
+
+impl Write for AsyncSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let shared = &*self.shared;
+        let mut state = shared.lock();
+        AsyncSink::take_error(&mut state)?;
+
+        if state.queue.len() >= shared.capacity {
+            match shared.policy {
+                OverflowPolicy::Block => {
+                    while state.queue.len() >= shared.capacity && !state.dead {
+                        state = shared
+                            .progress
+                            .wait(state)
+                            .unwrap_or_else(|err| err.into_inner());
+                    }
+                    if state.dead {
+                        AsyncSink::take_error(&mut state)?;
+                    }
+                }
+                OverflowPolicy::DropNewest => {
+                    shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Ok(buf.len());
+                }
+                OverflowPolicy::DropOldest => {
+                    state.queue.pop_front();
+                    shared.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        state.queue.push_back(buf.to_vec());
+        shared.work.notify_one();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let shared = &*self.shared;
+        let mut state = shared.lock();
+        state.flush_requested += 1;
+        let requested = state.flush_requested;
+        shared.work.notify_one();
+        while state.flushed < requested && !state.dead {
+            state = shared
+                .progress
+                .wait(state)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+
+        AsyncSink::take_error(&mut state)
+    }
+}
+
+impl Drop for AsyncSink {
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.work.notify_one();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsyncSink, OverflowPolicy};
+
+    use std::io;
+    use std::io::Write;
+    use std::sync::mpsc::{self, Receiver};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    // A sink that waits for a go-ahead before each write.
+    struct GatedSink {
+        gate: Receiver<()>,
+        written: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Write for GatedSink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let _ = self.gate.recv();
+            self.written.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // A sink that panics once given the go-ahead.
+    struct PanickingSink {
+        gate: Receiver<()>,
+    }
+
+    impl Write for PanickingSink {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            let _ = self.gate.recv();
+            panic!("sink failed");
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn gated(
+        policy: OverflowPolicy,
+    ) -> (AsyncSink, mpsc::Sender<()>, Arc<Mutex<Vec<u8>>>) {
+        let (open, gate) = mpsc::channel();
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let sink = AsyncSink::new(
+            GatedSink {
+                gate,
+                written: written.clone(),
+            },
+            1,
+            policy,
+        );
+
+        (sink, open, written)
+    }
+
+    // Wait until the writer thread has taken the first message.
+    fn wait_busy(sink: &AsyncSink) {
+        while !sink.shared.lock().busy {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn drop_newest() {
+        let (mut sink, open, written) = gated(OverflowPolicy::DropNewest);
+        let stats = sink.stats();
+        sink.write_all(b"a").unwrap();
+        wait_busy(&sink);
+        sink.write_all(b"b").unwrap();
+        sink.write_all(b"c").unwrap();
+        for _ in 0..3 {
+            open.send(()).unwrap();
+        }
+        sink.flush().unwrap();
+
+        assert_eq!(&*written.lock().unwrap(), b"ab");
+        assert_eq!(stats.dropped(), 1);
+    }
+
+    #[test]
+    fn drop_oldest() {
+        let (mut sink, open, written) = gated(OverflowPolicy::DropOldest);
+        let stats = sink.stats();
+        sink.write_all(b"a").unwrap();
+        wait_busy(&sink);
+        sink.write_all(b"b").unwrap();
+        sink.write_all(b"c").unwrap();
+        for _ in 0..3 {
+            open.send(()).unwrap();
+        }
+        drop(sink);
+
+        assert_eq!(&*written.lock().unwrap(), b"ac");
+        assert_eq!(stats.dropped(), 1);
+    }
+
+    #[test]
+    fn writer_panics() {
+        let (open, gate) = mpsc::channel();
+        let mut sink =
+            AsyncSink::new(PanickingSink { gate }, 1, OverflowPolicy::Block);
+        sink.write_all(b"a").unwrap();
+        wait_busy(&sink);
+        sink.write_all(b"b").unwrap();
+        let opener = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            open.send(()).unwrap();
+        });
+        // Blocks until the writer thread panics
+        assert!(sink.write_all(b"c").is_err());
+        opener.join().unwrap();
+        assert!(sink.flush().is_err());
+    }
+}
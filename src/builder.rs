@@ -0,0 +1,171 @@
+use error::Error;
+use error_policy::ErrorPolicy;
+use filter::Filter;
+use flush::FlushPolicy;
+use format::{Format, Timestamp};
+use output::Output;
+use {register, FlushGuard, SimpleLogger, LOGGER};
+
+use log;
+use log::LevelFilter;
+use std::io::Write;
+
+/// A complete logger configuration, installed at once with
+/// [`init()`](#method.init) or used to create an independent
+/// [`SimpleLogger`](struct.SimpleLogger.html) with [`build()`](#method.build).
+///
+/// Settings that are not given use their defaults: messages at `Info` or more
+/// severe are written to `stderr` in the default format, unbuffered, and write
+/// errors are only counted. Installing the configuration replaces all
+/// settings made earlier, including those made with
+/// [`set_format()`](fn.set_format.html),
+/// [`set_flush_policy()`](fn.set_flush_policy.html) and
+/// [`set_error_policy()`](fn.set_error_policy.html).
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::{Builder, FlushPolicy, Precision, Timestamp};
+/// use std::fs::File;
+/// use std::time::Duration;
+///
+/// # fn main() {
+/// Builder::new()
+///     .sink(File::create("test.log").unwrap())
+///     .level(LevelFilter::Debug)
+///     .timestamp(Timestamp::Utc(Precision::Millis))
+///     .flush_policy(FlushPolicy::Interval(Duration::from_secs(1)))
+///     .init()
+///     .unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Builder {
+    outputs: Vec<Output>,
+    filter: Filter,
+    format: Format,
+    flush_policy: FlushPolicy,
+    error_policy: ErrorPolicy,
+}
+
+impl Builder {
+    /// Create a configuration with the default settings.
+    pub fn new() -> Builder {
+        Builder {
+            outputs: Vec::new(),
+            filter: Filter::new(LevelFilter::Info),
+            format: Format::new(),
+            flush_policy: FlushPolicy::Unbuffered,
+            error_policy: ErrorPolicy::new(),
+        }
+    }
+
+    /// Add a sink. If no sinks or outputs are added, messages are written to
+    /// `stderr`.
+    pub fn sink<T: Write + Send + 'static>(self, sink: T) -> Builder {
+        self.output(Output::new(sink))
+    }
+
+    /// Add an output, with its own filter and format.
+    pub fn output(mut self, output: Output) -> Builder {
+        self.outputs.push(output);
+
+        self
+    }
+
+    /// Set the default level of the filter, keeping any per-target
+    /// directives.
+    pub fn level(mut self, level: LevelFilter) -> Builder {
+        self.filter = self.filter.default_level(level);
+
+        self
+    }
+
+    /// Set the filter, replacing any level set before.
+    pub fn filter<F: Into<Filter>>(mut self, filter: F) -> Builder {
+        self.filter = filter.into();
+
+        self
+    }
+
+    /// Set the format of log lines.
+    pub fn format(mut self, format: Format) -> Builder {
+        self.format = format;
+
+        self
+    }
+
+    /// Set how the time of each message is written, keeping the rest of the
+    /// format.
+    pub fn timestamp(mut self, timestamp: Timestamp) -> Builder {
+        self.format = self.format.timestamp(timestamp);
+
+        self
+    }
+
+    /// Set when messages are written to the sinks and the sinks flushed.
+    pub fn flush_policy(mut self, policy: FlushPolicy) -> Builder {
+        self.flush_policy = policy;
+
+        self
+    }
+
+    /// Set what to do when writing to a sink fails.
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Builder {
+        self.error_policy = policy;
+
+        self
+    }
+
+    /// Configure the [`log`](https://crates.io/crates/log) facade with these
+    /// settings.
+    ///
+    /// Fails without changing anything if a different logger has already been
+    /// set. With buffered or asynchronous sinks, use
+    /// [`init_with_guard()`](#method.init_with_guard) instead so messages
+    /// aren't lost when the program exits.
+    pub fn init(self) -> Result<(), Error> {
+        register()?;
+        log::set_max_level(self.apply(&LOGGER));
+
+        Ok(())
+    }
+
+    /// Like [`init()`](#method.init), but returns a guard that flushes and
+    /// shuts down the logger when dropped.
+    ///
+    /// See [`FlushGuard`](struct.FlushGuard.html) for details.
+    pub fn init_with_guard(self) -> Result<FlushGuard, Error> {
+        self.init()?;
+
+        Ok(FlushGuard { _private: () })
+    }
+
+    /// Create an independent logger with these settings, leaving the global
+    /// logger untouched.
+    pub fn build(self) -> SimpleLogger {
+        let logger = SimpleLogger::off();
+        self.apply(&logger);
+        logger
+    }
+
+    // Configure `logger`, returning the maximum level that can be logged.
+    fn apply(mut self, logger: &SimpleLogger) -> LevelFilter {
+        if self.outputs.is_empty() {
+            self.outputs.push(Output::stderr());
+        }
+        logger.set_format(self.format);
+        logger.set_flush_policy(self.flush_policy);
+        logger.set_error_policy(self.error_policy);
+        logger.renew(self.outputs, self.filter)
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
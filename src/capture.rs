@@ -0,0 +1,213 @@
+use log::Level;
+use std::io;
+use std::io::Write;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A sink that keeps log lines in memory, for testing.
+///
+/// Clones share the same buffer, so a clone can be handed to the logger while
+/// the original is used to inspect what was logged. Lines are expected in the
+/// default format described in the
+/// [crate documentation](index.html#log-format) and are parsed by
+/// [`records()`](#method.records).
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate log;
+/// extern crate simple_logging;
+///
+/// use log::{Level, LevelFilter};
+/// use simple_logging::Capture;
+///
+/// # fn main() {
+/// let capture = Capture::new();
+/// simple_logging::log_to(capture.clone(), LevelFilter::Info);
+///
+/// info!("connected");
+/// capture.assert_logged(Level::Info, "connected");
+/// capture.clear();
+/// assert!(capture.records().is_empty());
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Capture {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+/// A log line parsed by
+/// [`Capture::records()`](struct.Capture.html#method.records).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedRecord {
+    time: String,
+    thread: String,
+    level: Level,
+    message: String,
+}
+
+impl Capture {
+    /// Create an empty capture.
+    pub fn new() -> Capture {
+        Capture::default()
+    }
+
+    /// Everything written so far, with invalid UTF-8 replaced.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.lock()).into_owned()
+    }
+
+    /// The log lines written so far. Lines that don't start a new message,
+    /// such as those following an embedded newline, are appended to the
+    /// message of the previous line.
+    pub fn records(&self) -> Vec<CapturedRecord> {
+        let mut records: Vec<CapturedRecord> = Vec::new();
+        for line in self.text().lines() {
+            match CapturedRecord::parse(line) {
+                Some(record) => records.push(record),
+                None => {
+                    if let Some(record) = records.last_mut() {
+                        record.message.push('\n');
+                        record.message.push_str(line);
+                    }
+                }
+            }
+        }
+
+        records
+    }
+
+    /// Whether a message equal to `message` was logged at `level`.
+    pub fn contains(&self, level: Level, message: &str) -> bool {
+        self.records()
+            .iter()
+            .any(|record| record.level == level && record.message == message)
+    }
+
+    /// Panic unless a message equal to `message` was logged at `level`.
+    pub fn assert_logged(&self, level: Level, message: &str) {
+        if !self.contains(level, message) {
+            panic!(
+                "expected {} message {:?} to be logged, got:\n{}",
+                level,
+                message,
+                self.text()
+            );
+        }
+    }
+
+    /// Panic if a message equal to `message` was logged at `level`.
+    pub fn assert_not_logged(&self, level: Level, message: &str) {
+        if self.contains(level, message) {
+            panic!(
+                "expected {} message {:?} not to be logged, got:\n{}",
+                level,
+                message,
+                self.text()
+            );
+        }
+    }
+
+    /// Discard everything written so far.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<u8>> {
+        self.buffer.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CapturedRecord {
+    /// The time the message was logged, as written.
+    pub fn time(&self) -> &str {
+        &self.time
+    }
+
+    /// The thread identifier, as written.
+    pub fn thread(&self) -> &str {
+        &self.thread
+    }
+
+    /// The level of the message.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The message, including any columns before it and key-value pairs
+    /// after it.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    // Parse a line in the default format: `[time] (thread) level message`.
+    fn parse(line: &str) -> Option<CapturedRecord> {
+        if !line.starts_with('[') {
+            return None;
+        }
+        let end = line.find("] (")?;
+        let time = &line[1..end];
+        let rest = &line[end + 3..];
+        let end = rest.find(") ")?;
+        let thread = &rest[..end];
+        let rest = &rest[end + 2..];
+        // The level is padded to six characters, followed by a space
+        let level = rest.get(..6)?.trim_end().parse().ok()?;
+        let message = rest.get(7..).unwrap_or("");
+
+        Some(CapturedRecord {
+            time: time.to_owned(),
+            thread: thread.to_owned(),
+            level,
+            message: message.to_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Capture;
+
+    use log::Level;
+    use std::io::Write;
+
+    #[test]
+    fn records() {
+        let mut capture = Capture::new();
+        capture
+            .write_all(
+                concat!(
+                    "[00:00:01.000] (1a) INFO   first\n",
+                    "[00:00:02.000] (worker 1) ERROR  multi\n",
+                    "line\n",
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+
+        let records = capture.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].time(), "00:00:01.000");
+        assert_eq!(records[0].thread(), "1a");
+        assert_eq!(records[1].thread(), "worker 1");
+        assert_eq!(records[1].level(), Level::Error);
+        assert_eq!(records[1].message(), "multi\nline");
+        capture.assert_logged(Level::Info, "first");
+        capture.assert_not_logged(Level::Warn, "first");
+
+        capture.clear();
+        assert!(capture.records().is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+use log::Level;
+use std::env;
+use std::ffi::OsString;
+
+/// Whether an [`Output`](struct.Output.html) colors log lines with ANSI
+/// escape sequences.
+///
+/// Only the level is colored by default. Timestamps and targets can be
+/// colored too with
+/// [`Format::color_details()`](struct.Format.html#method.color_details). JSON
+/// and logfmt lines are never colored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color output to `stdout` and `stderr` if they are terminals. This can
+    /// be overridden with the `NO_COLOR` and `CLICOLOR_FORCE` environment
+    /// variables. Other sinks are never colored. This is the default.
+    #[default]
+    Auto,
+    /// Always color output.
+    Always,
+    /// Never color output.
+    Never,
+}
+
+pub(crate) const RESET: &str = "\x1b[0m";
+pub(crate) const DIM: &str = "\x1b[2m";
+pub(crate) const BOLD: &str = "\x1b[1m";
+
+// The escape sequence starting a message at `level`.
+pub(crate) fn level_style(level: Level) -> &'static str {
+    match level {
+        Level::Error => "\x1b[1;31m",
+        Level::Warn => "\x1b[33m",
+        Level::Info => "\x1b[32m",
+        Level::Debug => "\x1b[34m",
+        Level::Trace => "\x1b[36m",
+    }
+}
+
+// Whether to color output. `terminal` is whether the sink is a terminal, or
+// `None` if it isn't `stdout` or `stderr`.
+pub(crate) fn use_color(choice: ColorChoice, terminal: Option<bool>) -> bool {
+    decide(
+        choice,
+        terminal,
+        env::var_os("NO_COLOR"),
+        env::var_os("CLICOLOR_FORCE"),
+    )
+}
+
+fn decide(
+    choice: ColorChoice,
+    terminal: Option<bool>,
+    no_color: Option<OsString>,
+    force: Option<OsString>,
+) -> bool {
+    let terminal = match choice {
+        ColorChoice::Always => return true,
+        ColorChoice::Never => return false,
+        ColorChoice::Auto => match terminal {
+            Some(terminal) => terminal,
+            None => return false,
+        },
+    };
+
+    if no_color.is_some_and(|value| !value.is_empty()) {
+        false
+    } else if force.is_some_and(|value| value != "0") {
+        true
+    } else {
+        terminal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decide, ColorChoice};
+
+    use std::ffi::OsString;
+
+    #[test]
+    fn decide_auto() {
+        let set = |value: &str| Some(OsString::from(value));
+        let auto = ColorChoice::Auto;
+
+        assert!(decide(auto, Some(true), None, None));
+        assert!(!decide(auto, Some(false), None, None));
+        assert!(!decide(auto, None, None, set("1")));
+        assert!(!decide(auto, Some(true), set("1"), None));
+        assert!(decide(auto, Some(true), set(""), None));
+        assert!(decide(auto, Some(false), None, set("1")));
+        assert!(!decide(auto, Some(false), None, set("0")));
+        assert!(!decide(auto, Some(false), set("1"), set("1")));
+        assert!(decide(ColorChoice::Always, None, set("1"), None));
+        assert!(!decide(ColorChoice::Never, Some(true), None, set("1")));
+    }
+}
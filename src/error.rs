@@ -0,0 +1,41 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+/// The error returned by the `try_log_to*()` functions.
+#[derive(Debug)]
+pub enum Error {
+    /// A different logger has already been set for the
+    /// [`log`](https://crates.io/crates/log) facade.
+    LoggerAlreadySet,
+    /// Opening the sink failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::LoggerAlreadySet => {
+                write!(f, "a different logger has already been set")
+            }
+            Error::Io(ref err) => {
+                write!(f, "failed to open the log sink: {}", err)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::LoggerAlreadySet => None,
+            Error::Io(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
@@ -0,0 +1,183 @@
+use std::cell::Cell;
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::sync::Arc;
+
+type Callback = Arc<dyn Fn(&io::Error) + Send + Sync>;
+
+thread_local! {
+    // Whether an error callback is running on this thread
+    static REPORTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// What to do when writing to the sink fails.
+///
+/// By default, errors are only counted (see
+/// [`failed_writes()`](fn.failed_writes.html)). A callback can be called on
+/// every error, and a fallback sink can replace the sink after a number of
+/// consecutive failures.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::ErrorPolicy;
+/// use std::io;
+///
+/// # fn main() {
+/// simple_logging::set_error_policy(
+///     ErrorPolicy::new()
+///         .on_error(|err| eprintln!("failed to write log message: {}", err))
+///         .fallback(3, io::stderr()),
+/// );
+/// simple_logging::log_to_file("test.log", LevelFilter::Info);
+/// # }
+/// ```
+#[derive(Default)]
+pub struct ErrorPolicy {
+    callback: Option<Callback>,
+    fallback: Option<(usize, Box<dyn Write + Send>)>,
+}
+
+impl ErrorPolicy {
+    /// Create a policy that only counts errors.
+    pub fn new() -> ErrorPolicy {
+        ErrorPolicy::default()
+    }
+
+    /// Call `callback` with every error. The callback is called after the
+    /// logger is unlocked, so it may log messages itself. Errors writing those
+    /// messages are counted but not reported to the callback again.
+    pub fn on_error<F: Fn(&io::Error) + Send + Sync + 'static>(
+        mut self,
+        callback: F,
+    ) -> ErrorPolicy {
+        self.callback = Some(Arc::new(callback));
+
+        self
+    }
+
+    /// Replace the sink with `sink` after `failures` consecutive failed writes
+    /// (at least one). The message that failed last is written again to the
+    /// new sink, but previously failed messages are lost. When logging to
+    /// several outputs, only the first one to fail that many times is replaced.
+    pub fn fallback<T: Write + Send + 'static>(
+        mut self,
+        failures: usize,
+        sink: T,
+    ) -> ErrorPolicy {
+        self.fallback = Some((failures.max(1), Box::new(sink)));
+
+        self
+    }
+}
+
+impl fmt::Debug for ErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ErrorPolicy")
+            .field("callback", &self.callback.is_some())
+            .field(
+                "fallback",
+                &self.fallback.as_ref().map(|fallback| fallback.0),
+            )
+            .finish()
+    }
+}
+
+// Error bookkeeping for a logger.
+pub(crate) struct Errors {
+    policy: ErrorPolicy,
+    failed: u64,
+}
+
+// A failed write, to be reported once the logger is unlocked.
+pub(crate) struct Failure {
+    error: io::Error,
+    callback: Option<Callback>,
+    // Whether the sink was replaced by the fallback sink
+    pub switched: bool,
+}
+
+impl Errors {
+    pub fn new() -> Errors {
+        Errors {
+            policy: ErrorPolicy::new(),
+            failed: 0,
+        }
+    }
+
+    // Change the policy. Counts of consecutive failures should be reset too.
+    pub fn set_policy(&mut self, policy: ErrorPolicy) {
+        self.policy = policy;
+    }
+
+    // Total number of failed writes.
+    pub fn failed(&self) -> u64 {
+        self.failed
+    }
+
+    // Account for the result of writing to `sink`, replacing it with the
+    // fallback sink if needed. `consecutive` counts the consecutive failures
+    // of `sink`.
+    pub fn check<T>(
+        &mut self,
+        result: io::Result<T>,
+        consecutive: &mut usize,
+        sink: &mut Box<dyn Write + Send>,
+    ) -> Option<Failure> {
+        let error = match result {
+            Ok(_) => {
+                *consecutive = 0;
+                return None;
+            }
+            Err(error) => error,
+        };
+
+        self.failed += 1;
+        *consecutive += 1;
+        let switch = match self.policy.fallback {
+            Some((failures, _)) => *consecutive >= failures,
+            None => false,
+        };
+        if switch {
+            if let Some((_, fallback)) = self.policy.fallback.take() {
+                *sink = fallback;
+            }
+            *consecutive = 0;
+        }
+
+        Some(Failure {
+            error,
+            callback: self.policy.callback.clone(),
+            switched: switch,
+        })
+    }
+}
+
+impl Failure {
+    // Call the error callback, if any, unless this failure was caused by a
+    // message logged from the callback itself.
+    pub fn report(self) {
+        let callback = match self.callback {
+            Some(callback) => callback,
+            None => return,
+        };
+        if REPORTING.with(|reporting| reporting.replace(true)) {
+            return;
+        }
+        let _reset = Reset;
+        callback(&self.error);
+    }
+}
+
+// Clears `REPORTING` when dropped, even if the callback panics.
+struct Reset;
+
+impl Drop for Reset {
+    fn drop(&mut self) {
+        REPORTING.with(|reporting| reporting.set(false));
+    }
+}
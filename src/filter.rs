@@ -0,0 +1,361 @@
+use log::{LevelFilter, Metadata, Record};
+#[cfg(feature = "regex")]
+use regex::Regex;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A table of per-target level directives.
+///
+/// Each directive maps a target prefix to a maximum log level. When deciding
+/// whether a message should be logged, the directive with the longest target
+/// matching the message target is used. A directive matches a target if it is
+/// equal to it or if it is a parent module of it, so `my_app::db` matches
+/// `my_app::db` and `my_app::db::pool`, but not `my_app::dbx`. Messages whose
+/// target matches no directive fall back to the default level.
+///
+/// A `LevelFilter` converts into a `Filter` with no directives, so plain
+/// levels can be used wherever a `Filter` is expected.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::Filter;
+///
+/// # fn main() {
+/// let filter = Filter::new(LevelFilter::Info)
+///     .directive("hyper", LevelFilter::Warn)
+///     .directive("my_app::db", LevelFilter::Trace);
+/// # }
+/// ```
+///
+/// Filters can also be parsed from a comma-separated list of `target=level`
+/// directives, where a bare level sets the default level:
+///
+/// ```rust
+/// # extern crate simple_logging;
+/// use simple_logging::Filter;
+///
+/// # fn main() {
+/// let filter: Filter = "hyper=warn,my_app::db=trace,info".parse().unwrap();
+/// # }
+/// ```
+///
+/// The string syntax is compatible with `RUST_LOG`, including an optional
+/// `/pattern` suffix that only lets through messages matching `pattern`. The
+/// pattern is a regular expression if the `regex` feature is enabled and a
+/// plain substring otherwise, in which case patterns containing regular
+/// expression metacharacters are rejected.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    level: LevelFilter,
+    // Sorted by descending target length so the first match is the longest
+    directives: Vec<Directive>,
+    message: Option<MessageFilter>,
+}
+
+#[derive(Clone, Debug)]
+struct Directive {
+    target: String,
+    level: LevelFilter,
+}
+
+impl Directive {
+    fn matches(&self, target: &str) -> bool {
+        target.starts_with(&*self.target)
+            && (target.len() == self.target.len()
+                || target[self.target.len()..].starts_with("::"))
+    }
+}
+
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+struct MessageFilter(Regex);
+
+#[cfg(feature = "regex")]
+impl MessageFilter {
+    fn new(pattern: &str) -> Result<MessageFilter, ParseFilterError> {
+        Regex::new(pattern)
+            .map(MessageFilter)
+            .map_err(|_| ParseFilterError::new(pattern))
+    }
+
+    fn is_match(&self, message: &str) -> bool {
+        self.0.is_match(message)
+    }
+}
+
+#[cfg(not(feature = "regex"))]
+#[derive(Clone, Debug)]
+struct MessageFilter(String);
+
+// Characters with a special meaning in regular expressions. Patterns using
+// them are rejected without the `regex` feature, instead of being matched
+// literally and silently filtering out every message.
+#[cfg(not(feature = "regex"))]
+const METACHARACTERS: &str = "\\.+*?()|[]{}^$";
+
+#[cfg(not(feature = "regex"))]
+impl MessageFilter {
+    fn new(pattern: &str) -> Result<MessageFilter, ParseFilterError> {
+        if pattern.contains(|c| METACHARACTERS.contains(c)) {
+            return Err(ParseFilterError::new(pattern));
+        }
+
+        Ok(MessageFilter(pattern.to_owned()))
+    }
+
+    fn is_match(&self, message: &str) -> bool {
+        message.contains(&*self.0)
+    }
+}
+
+impl Filter {
+    /// Create a new `Filter` with the given default level and no directives.
+    pub fn new(level: LevelFilter) -> Filter {
+        Filter {
+            level,
+            directives: Vec::new(),
+            message: None,
+        }
+    }
+
+    /// Parse a filter from the environment variable `var`, using the same
+    /// syntax as [`FromStr`](#impl-FromStr). If the variable is not set,
+    /// `default` is used instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate log;
+    /// # extern crate simple_logging;
+    /// use log::LevelFilter;
+    /// use simple_logging::Filter;
+    ///
+    /// # fn main() {
+    /// let filter = Filter::from_env("RUST_LOG", LevelFilter::Info).unwrap();
+    /// # }
+    /// ```
+    pub fn from_env<F: Into<Filter>>(
+        var: &str,
+        default: F,
+    ) -> Result<Filter, ParseFilterError> {
+        match env::var(var) {
+            Ok(spec) => spec.parse(),
+            Err(_) => Ok(default.into()),
+        }
+    }
+
+    /// Add a directive limiting messages with the given target (or any of its
+    /// children) to `level`. Replaces any previous directive for the same
+    /// target.
+    pub fn directive<T: Into<String>>(
+        mut self,
+        target: T,
+        level: LevelFilter,
+    ) -> Filter {
+        let target = target.into();
+        self.directives
+            .retain(|directive| directive.target != target);
+        let index = self
+            .directives
+            .iter()
+            .position(|directive| directive.target.len() < target.len())
+            .unwrap_or(self.directives.len());
+        self.directives.insert(index, Directive { target, level });
+
+        self
+    }
+
+    /// Set the default level used for targets not matching any directive.
+    pub fn default_level(mut self, level: LevelFilter) -> Filter {
+        self.level = level;
+
+        self
+    }
+
+    /// The most verbose level this filter lets through for any target.
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|directive| directive.level)
+            .fold(self.level, |max, level| max.max(level))
+    }
+
+    /// The level applicable to the given target.
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .find(|directive| directive.matches(target))
+            .map_or(self.level, |directive| directive.level)
+    }
+
+    /// Only let through messages matching `pattern`. The pattern is a regular
+    /// expression if the `regex` feature is enabled and a plain substring
+    /// otherwise, in which case patterns containing regular expression
+    /// metacharacters such as `^` or `.` are rejected.
+    pub fn message(
+        mut self,
+        pattern: &str,
+    ) -> Result<Filter, ParseFilterError> {
+        self.message = Some(MessageFilter::new(pattern)?);
+
+        Ok(self)
+    }
+
+    /// Whether a message with the given metadata passes this filter, without
+    /// taking the message pattern into account.
+    pub fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    /// Whether a record passes this filter, including the message pattern.
+    pub fn matches(&self, record: &Record) -> bool {
+        if !self.enabled(record.metadata()) {
+            return false;
+        }
+
+        match self.message {
+            Some(ref message) => match record.args().as_str() {
+                Some(args) => message.is_match(args),
+                None => message.is_match(&record.args().to_string()),
+            },
+            None => true,
+        }
+    }
+}
+
+impl From<LevelFilter> for Filter {
+    fn from(level: LevelFilter) -> Filter {
+        Filter::new(level)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = ParseFilterError;
+
+    /// Parse a comma-separated list of directives, optionally followed by
+    /// `/pattern`. Each directive is either `target=level`, a bare `level`
+    /// setting the default level, or a bare `target` enabling all levels for
+    /// that target. The default level is `off` unless set explicitly.
+    fn from_str(spec: &str) -> Result<Filter, ParseFilterError> {
+        let mut parts = spec.splitn(2, '/');
+        let directives = parts.next().unwrap();
+        let mut filter = Filter::new(LevelFilter::Off);
+        if let Some(pattern) = parts.next() {
+            filter = filter.message(pattern)?;
+        }
+
+        for directive in directives.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+
+            let mut parts = directive.splitn(2, '=');
+            let target = parts.next().unwrap().trim();
+            filter = match parts.next().map(str::trim) {
+                Some(level) => match level.parse() {
+                    Ok(level) if !target.is_empty() => {
+                        filter.directive(target, level)
+                    }
+                    _ => return Err(ParseFilterError::new(directive)),
+                },
+                None => match target.parse() {
+                    Ok(level) => filter.default_level(level),
+                    Err(_) => filter.directive(target, LevelFilter::Trace),
+                },
+            };
+        }
+
+        Ok(filter)
+    }
+}
+
+/// The error returned when parsing a [`Filter`](struct.Filter.html) fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseFilterError {
+    directive: String,
+}
+
+impl ParseFilterError {
+    fn new(directive: &str) -> ParseFilterError {
+        ParseFilterError {
+            directive: directive.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ParseFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid log directive `{}`", self.directive)
+    }
+}
+
+impl Error for ParseFilterError {}
+
+#[cfg(test)]
+mod tests {
+    use super::Filter;
+
+    use log::Level;
+    use log::LevelFilter::{Debug, Info, Off, Trace, Warn};
+    use log::{Metadata, Record};
+
+    fn enabled(filter: &Filter, target: &str, level: Level) -> bool {
+        filter.enabled(&Metadata::builder().target(target).level(level).build())
+    }
+
+    #[test]
+    fn longest_prefix() {
+        let filter = Filter::new(Info)
+            .directive("my_app", Warn)
+            .directive("my_app::db", Trace);
+
+        assert!(enabled(&filter, "other", Level::Info));
+        assert!(!enabled(&filter, "other", Level::Debug));
+        assert!(!enabled(&filter, "my_app", Level::Info));
+        assert!(enabled(&filter, "my_app::db", Level::Trace));
+        assert!(enabled(&filter, "my_app::db::pool", Level::Trace));
+        assert!(!enabled(&filter, "my_app::dbx", Level::Info));
+        assert_eq!(filter.max_level(), Trace);
+    }
+
+    #[test]
+    fn parse() {
+        let filter: Filter =
+            "hyper=warn, my_app::db=TRACE,debug,tokio".parse().unwrap();
+
+        assert_eq!(filter.level_for("other"), Debug);
+        assert_eq!(filter.level_for("hyper::client"), Warn);
+        assert_eq!(filter.level_for("my_app::db"), Trace);
+        assert_eq!(filter.level_for("tokio"), Trace);
+        assert_eq!("".parse::<Filter>().unwrap().max_level(), Off);
+        assert!("hyper=loud".parse::<Filter>().is_err());
+        assert!("=info".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn message() {
+        let filter: Filter = "info/connected".parse().unwrap();
+        let matches = |message: &str| {
+            filter.matches(
+                &Record::builder()
+                    .level(Level::Info)
+                    .args(format_args!("{}", message))
+                    .build(),
+            )
+        };
+
+        assert!(matches("connected"));
+        assert!(!matches("disconnecting"));
+        assert_eq!(filter.max_level(), Info);
+        #[cfg(not(feature = "regex"))]
+        assert!("info/^conn".parse::<Filter>().is_err());
+        #[cfg(feature = "regex")]
+        assert!("info/^conn".parse::<Filter>().is_ok());
+    }
+}
@@ -0,0 +1,183 @@
+use log::Level;
+use std::io;
+use std::io::Write;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Buffered messages are written to the sink once they reach this size, even if
+// the flush policy doesn't call for a flush yet.
+const CAPACITY: usize = 8 * 1024;
+
+/// When buffered messages are written out and the sink is flushed.
+///
+/// Except for `Unbuffered`, messages are collected in an internal buffer and
+/// written to the sink on flushes, or whenever the buffer grows past 8KiB.
+/// Messages are never split between writes. Flushes can also be requested at
+/// any time through `log::logger().flush()`.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::{Level, LevelFilter};
+/// use simple_logging::FlushPolicy;
+///
+/// # fn main() {
+/// simple_logging::set_flush_policy(FlushPolicy::AtLevel(Level::Warn));
+/// simple_logging::log_to_file("test.log", LevelFilter::Info);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// Don't buffer messages, writing each one straight to the sink. The sink
+    /// is only flushed on request. This is the default.
+    Unbuffered,
+    /// Flush after every message.
+    EveryRecord,
+    /// Flush after messages at the given level or more severe.
+    AtLevel(Level),
+    /// Flush once at least the given number of bytes are buffered.
+    EveryBytes(usize),
+    /// Flush buffered messages at least this often. A background thread
+    /// flushes them at every interval, and logging a message flushes them if
+    /// the interval has passed since the last flush. A zero interval flushes
+    /// after every message like `EveryRecord`, without a background thread.
+    Interval(Duration),
+}
+
+// Messages waiting to be written to the sink.
+pub(crate) struct Buffer {
+    policy: FlushPolicy,
+    pending: Vec<u8>,
+    last_flush: Instant,
+}
+
+impl Buffer {
+    pub fn new() -> Buffer {
+        Buffer {
+            policy: FlushPolicy::Unbuffered,
+            pending: Vec::new(),
+            last_flush: Instant::now(),
+        }
+    }
+
+    // Change the flush policy. Pending messages should be flushed first.
+    pub fn set_policy(&mut self, policy: FlushPolicy) {
+        self.policy = policy;
+    }
+
+    // Write a formatted message, buffering it according to the flush policy.
+    pub fn write(
+        &mut self,
+        sink: &mut dyn Write,
+        message: &[u8],
+        level: Level,
+    ) -> io::Result<()> {
+        let flush = match self.policy {
+            FlushPolicy::Unbuffered => return sink.write_all(message),
+            FlushPolicy::EveryRecord => true,
+            FlushPolicy::AtLevel(at) => level <= at,
+            FlushPolicy::EveryBytes(bytes) => {
+                self.pending.len() + message.len() >= bytes
+            }
+            FlushPolicy::Interval(interval) => {
+                self.last_flush.elapsed() >= interval
+            }
+        };
+
+        if self.pending.len() + message.len() > CAPACITY {
+            self.write_pending(sink)?;
+        }
+        self.pending.extend_from_slice(message);
+        if flush {
+            self.flush(sink)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Whether there are messages waiting to be written.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    // Write all pending messages and flush the sink.
+    pub fn flush(&mut self, sink: &mut dyn Write) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        self.write_pending(sink)?;
+        sink.flush()
+    }
+
+    fn write_pending(&mut self, sink: &mut dyn Write) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let result = sink.write_all(&self.pending);
+        self.pending.clear();
+        result
+    }
+}
+
+// A background thread calling a function at a fixed interval until the timer
+// is dropped or the function returns `false`.
+pub(crate) struct Timer {
+    // Disconnected when the timer is dropped, which stops the thread
+    _stop: Sender<()>,
+}
+
+impl Timer {
+    pub fn start<F>(interval: Duration, mut tick: F) -> Timer
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        let (stop, stopped) = mpsc::channel::<()>();
+        thread::Builder::new()
+            .name("simple-logging-flush".to_owned())
+            .spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) =
+                    stopped.recv_timeout(interval)
+                {
+                    if !tick() {
+                        return;
+                    }
+                }
+            })
+            .expect("failed to spawn the log flushing thread");
+
+        Timer { _stop: stop }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Buffer, FlushPolicy};
+
+    use log::Level;
+
+    #[test]
+    fn at_level() {
+        let mut buffer = Buffer::new();
+        buffer.set_policy(FlushPolicy::AtLevel(Level::Warn));
+        let mut sink = Vec::new();
+
+        buffer.write(&mut sink, b"a\n", Level::Info).unwrap();
+        assert!(sink.is_empty());
+        buffer.write(&mut sink, b"b\n", Level::Error).unwrap();
+        assert_eq!(sink, b"a\nb\n");
+    }
+
+    #[test]
+    fn every_bytes() {
+        let mut buffer = Buffer::new();
+        buffer.set_policy(FlushPolicy::EveryBytes(4));
+        let mut sink = Vec::new();
+
+        buffer.write(&mut sink, b"a\n", Level::Error).unwrap();
+        assert!(sink.is_empty());
+        buffer.write(&mut sink, b"b\n", Level::Info).unwrap();
+        assert_eq!(sink, b"a\nb\n");
+    }
+}
@@ -0,0 +1,878 @@
+use color;
+use kv;
+use sanitize::{Sanitize, Sanitizer};
+use template::{Align, Field, Piece, Template};
+use time::DateTime;
+
+use log::{Level, Record};
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::str;
+use std::thread::{self, Thread};
+use std::time::{Instant, SystemTime};
+
+/// How the time of each log message is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Timestamp {
+    /// Time elapsed since the logger was configured, as
+    /// `<hh>:<mm>:<ss>.<SSS>`. This is the default.
+    Uptime,
+    /// Wall-clock time in UTC, in RFC 3339 format (e.g.
+    /// `2018-12-29T14:02:07.123Z`).
+    Utc(Precision),
+    /// Wall-clock local time, in RFC 3339 format with the offset from UTC
+    /// (e.g. `2018-12-29T15:02:07.123+01:00`). Falls back to UTC on platforms
+    /// where the local offset can't be determined. The offset is read from
+    /// the system time zone settings, including the `TZ` environment
+    /// variable, so the environment must not be modified while logging from
+    /// other threads.
+    Local(Precision),
+}
+
+/// Number of fractional second digits in wall-clock timestamps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precision {
+    /// Whole seconds.
+    Seconds,
+    /// Three fractional digits.
+    Millis,
+    /// Six fractional digits.
+    Micros,
+    /// Nine fractional digits.
+    Nanos,
+}
+
+impl Precision {
+    fn digits(self) -> usize {
+        match self {
+            Precision::Seconds => 0,
+            Precision::Millis => 3,
+            Precision::Micros => 6,
+            Precision::Nanos => 9,
+        }
+    }
+}
+
+/// How the thread that logged each message is identified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThreadLabel {
+    /// The implementation-specific thread ID, in hex. This is the default.
+    Id,
+    /// The thread name, as given to
+    /// [`thread::Builder::name()`](https://doc.rust-lang.org/std/thread/struct.Builder.html#method.name).
+    /// Falls back to the ID for unnamed threads.
+    Name,
+}
+
+/// The format of each log line.
+///
+/// By default, lines follow the format described in the
+/// [crate documentation](index.html#log-format). A custom layout can be set
+/// with a [`Template`](struct.Template.html), and structured formats are
+/// available through [`Format::json()`](#method.json) and
+/// [`Format::logfmt()`](#method.logfmt).
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::{Format, Precision, Timestamp};
+///
+/// # fn main() {
+/// simple_logging::set_format(
+///     Format::new().timestamp(Timestamp::Utc(Precision::Millis)),
+/// );
+/// simple_logging::log_to_stderr(LevelFilter::Info);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Format {
+    layout: Layout,
+    timestamp: Timestamp,
+    thread: ThreadLabel,
+    target: bool,
+    module: bool,
+    location: bool,
+    short_paths: bool,
+    sanitize: Sanitize,
+    color_details: bool,
+}
+
+#[derive(Clone, Debug)]
+enum Layout {
+    Text,
+    Template(Template),
+    Json,
+    Logfmt,
+}
+
+impl Format {
+    /// Create the default format, as described in the
+    /// [crate documentation](index.html#log-format).
+    pub fn new() -> Format {
+        Format {
+            layout: Layout::Text,
+            timestamp: Timestamp::Uptime,
+            thread: ThreadLabel::Id,
+            target: false,
+            module: false,
+            location: false,
+            short_paths: false,
+            sanitize: Sanitize::Off,
+            color_details: false,
+        }
+    }
+
+    /// Create a [JSON Lines](http://jsonlines.org/) format, where each message
+    /// is written as a single-line JSON object:
+    ///
+    /// ```text
+    /// {"ts":"2018-12-29T14:02:07.123Z","level":"INFO","thread":"1a","target":"app","module":"app::db","file":"src/db.rs","line":42,"msg":"connected"}
+    /// ```
+    ///
+    /// `module`, `file` and `line` are `null` if unknown. Timestamps default
+    /// to UTC with millisecond precision. With the `kv` feature, key-value
+    /// pairs are added as members, with keys that clash with the members
+    /// above or start with `kv.` prefixed with `kv.`.
+    pub fn json() -> Format {
+        Format {
+            layout: Layout::Json,
+            timestamp: Timestamp::Utc(Precision::Millis),
+            ..Format::new()
+        }
+    }
+
+    /// Create a [logfmt](https://brandur.org/logfmt) format, where each
+    /// message is written as a line of `key=value` pairs:
+    ///
+    /// ```text
+    /// ts=2018-12-29T14:02:07.123Z level=info thread=1a target=app msg="connected to db"
+    /// ```
+    ///
+    /// Values are quoted if they are empty or contain spaces, `=`, `"` or
+    /// control characters, with quotes, backslashes and control characters
+    /// escaped. Timestamps default to UTC with millisecond precision. With the
+    /// `kv` feature, key-value pairs are appended, with keys that clash with
+    /// the pairs above or start with `kv.` prefixed with `kv.`.
+    pub fn logfmt() -> Format {
+        Format {
+            layout: Layout::Logfmt,
+            timestamp: Timestamp::Utc(Precision::Millis),
+            ..Format::new()
+        }
+    }
+
+    /// Set how the time of each message is written.
+    pub fn timestamp(mut self, timestamp: Timestamp) -> Format {
+        self.timestamp = timestamp;
+
+        self
+    }
+
+    /// Set how the thread that logged each message is identified.
+    pub fn thread(mut self, thread: ThreadLabel) -> Format {
+        self.thread = thread;
+
+        self
+    }
+
+    /// Write the target of each message before the message itself in the
+    /// default format.
+    pub fn target(mut self, target: bool) -> Format {
+        self.target = target;
+
+        self
+    }
+
+    /// Write the module path where each message was logged before the
+    /// message itself in the default format.
+    pub fn module(mut self, module: bool) -> Format {
+        self.module = module;
+
+        self
+    }
+
+    /// Write the source location (`<file>:<line>`) where each message was
+    /// logged before the message itself in the default format.
+    pub fn location(mut self, location: bool) -> Format {
+        self.location = location;
+
+        self
+    }
+
+    /// Shorten source file paths to start at the `src` directory of their
+    /// crate, so that the absolute paths of dependencies such as
+    /// `/home/me/.cargo/registry/src/.../log-0.4.6/src/lib.rs` are written as
+    /// `src/lib.rs`. Applies to all formats.
+    pub fn short_paths(mut self, short_paths: bool) -> Format {
+        self.short_paths = short_paths;
+
+        self
+    }
+
+    /// Set how control characters in messages, targets and thread names are
+    /// written, such as embedded newlines. See
+    /// [`Sanitize`](enum.Sanitize.html).
+    pub fn sanitize(mut self, sanitize: Sanitize) -> Format {
+        self.sanitize = sanitize;
+
+        self
+    }
+
+    /// Lay out each line according to `template` instead of the default
+    /// format.
+    pub fn template(mut self, template: Template) -> Format {
+        self.layout = Layout::Template(template);
+
+        self
+    }
+
+    /// When output is colored, also dim timestamps and write targets and
+    /// module paths in bold. See [`ColorChoice`](enum.ColorChoice.html).
+    pub fn color_details(mut self, color_details: bool) -> Format {
+        self.color_details = color_details;
+
+        self
+    }
+
+    // Write `record` as a single line. `start` is the time the logger was
+    // configured. If `color` is `true`, text lines are colored with ANSI
+    // escape sequences.
+    pub(crate) fn write(
+        &self,
+        out: &mut Vec<u8>,
+        start: Instant,
+        record: &Record,
+        color: bool,
+    ) -> io::Result<()> {
+        match self.layout {
+            Layout::Text => {
+                write!(out, "[")?;
+                let style = self.style(Field::Time, record.level(), color);
+                paint(out, style, |out| self.write_time(out, start))?;
+                write!(out, "] (")?;
+                self.write_thread(out)?;
+                write!(out, ") ")?;
+                let style = self.style(Field::Level, record.level(), color);
+                paint(out, style, |out| write!(out, "{:6}", record.level()))?;
+                write!(out, " ")?;
+                self.write_columns(out, record, color)?;
+                self.write_message(out, record);
+                kv::write_logfmt(out, record)?;
+                writeln!(out)
+            }
+            Layout::Template(ref template) => {
+                for piece in template.pieces() {
+                    match *piece {
+                        Piece::Literal(ref literal) => {
+                            out.write_all(literal.as_bytes())?
+                        }
+                        Piece::Field(field, align, width) => {
+                            let style =
+                                self.style(field, record.level(), color);
+                            paint(out, style, |out| {
+                                let mark = out.len();
+                                self.write_field(out, field, start, record)?;
+                                pad(out, mark, align, width);
+                                Ok(())
+                            })?;
+                        }
+                    }
+                }
+                writeln!(out)
+            }
+            Layout::Json => self.write_json(out, start, record),
+            Layout::Logfmt => self.write_logfmt(out, start, record),
+        }
+    }
+
+    fn write_json(
+        &self,
+        out: &mut Vec<u8>,
+        start: Instant,
+        record: &Record,
+    ) -> io::Result<()> {
+        write!(out, "{{\"ts\":\"")?;
+        self.write_time(out, start)?;
+        write!(out, "\",\"level\":\"{}\",\"thread\":", record.level())?;
+        match self.thread_name(&thread::current()) {
+            Some(name) => write_json_string(out, name)?,
+            None => write!(out, "\"{:x}\"", thread_id::get())?,
+        }
+        write!(out, ",\"target\":")?;
+        write_json_string(out, record.target())?;
+        write!(out, ",\"module\":")?;
+        write_json_optional(out, record.module_path())?;
+        write!(out, ",\"file\":")?;
+        write_json_optional(out, self.file(record))?;
+        match record.line() {
+            Some(line) => write!(out, ",\"line\":{}", line)?,
+            None => write!(out, ",\"line\":null")?,
+        }
+        write!(out, ",\"msg\":\"")?;
+        let _ = fmt::write(&mut JsonEscape(out), *record.args());
+        write!(out, "\"")?;
+        kv::write_json(out, record)?;
+        writeln!(out, "}}")
+    }
+
+    fn write_logfmt(
+        &self,
+        out: &mut Vec<u8>,
+        start: Instant,
+        record: &Record,
+    ) -> io::Result<()> {
+        write!(out, "ts=")?;
+        self.write_time(out, start)?;
+        write!(
+            out,
+            " level={} thread=",
+            record.level().as_str().to_ascii_lowercase()
+        )?;
+        match self.thread_name(&thread::current()) {
+            Some(name) => write_logfmt_value(out, name)?,
+            None => write!(out, "{:x}", thread_id::get())?,
+        }
+        write!(out, " target=")?;
+        write_logfmt_value(out, record.target())?;
+        write!(out, " msg=")?;
+        match record.args().as_str() {
+            Some(message) => write_logfmt_value(out, message)?,
+            None => write_logfmt_value(out, &record.args().to_string())?,
+        }
+        kv::write_logfmt_fields(out, record)?;
+        writeln!(out)
+    }
+
+    fn write_field(
+        &self,
+        out: &mut Vec<u8>,
+        field: Field,
+        start: Instant,
+        record: &Record,
+    ) -> io::Result<()> {
+        match field {
+            Field::Time => self.write_time(out, start),
+            Field::Elapsed => write_uptime(out, start),
+            Field::Wall => match self.timestamp {
+                Timestamp::Uptime => {
+                    write_wall(out, DateTime::utc(SystemTime::now()), 3)
+                }
+                _ => self.write_time(out, start),
+            },
+            Field::Thread => self.write_thread(out),
+            Field::ThreadId => write!(out, "{:x}", thread_id::get()),
+            Field::ThreadName => match thread::current().name() {
+                Some(name) => self.write_sanitized(out, name),
+                None => write!(out, "{:x}", thread_id::get()),
+            },
+            Field::Level => write!(out, "{}", record.level()),
+            Field::Target => self.write_sanitized(out, record.target()),
+            Field::Module => self.write_optional(out, record.module_path()),
+            Field::File => self.write_optional(out, self.file(record)),
+            Field::Line => match record.line() {
+                Some(line) => write!(out, "{}", line),
+                None => write!(out, "-"),
+            },
+            Field::Message => {
+                self.write_message(out, record);
+                Ok(())
+            }
+            Field::KeyValues => kv::write_logfmt(out, record),
+        }
+    }
+
+    fn write_message(&self, out: &mut Vec<u8>, record: &Record) {
+        let mut sanitizer = Sanitizer {
+            out,
+            mode: self.sanitize,
+        };
+        let _ = fmt::write(&mut sanitizer, *record.args());
+    }
+
+    fn write_sanitized(
+        &self,
+        out: &mut Vec<u8>,
+        value: &str,
+    ) -> io::Result<()> {
+        let mut sanitizer = Sanitizer {
+            out,
+            mode: self.sanitize,
+        };
+        let _ = fmt::Write::write_str(&mut sanitizer, value);
+        Ok(())
+    }
+
+    fn write_optional(
+        &self,
+        out: &mut Vec<u8>,
+        value: Option<&str>,
+    ) -> io::Result<()> {
+        self.write_sanitized(out, value.unwrap_or("-"))
+    }
+
+    // Write the optional columns of the default format, followed by `: `.
+    fn write_columns(
+        &self,
+        out: &mut Vec<u8>,
+        record: &Record,
+        color: bool,
+    ) -> io::Result<()> {
+        let mark = out.len();
+        if self.target {
+            let style = self.style(Field::Target, record.level(), color);
+            paint(out, style, |out| {
+                self.write_sanitized(out, record.target())
+            })?;
+            write!(out, " ")?;
+        }
+        if self.module {
+            let style = self.style(Field::Module, record.level(), color);
+            paint(out, style, |out| {
+                self.write_optional(out, record.module_path())
+            })?;
+            write!(out, " ")?;
+        }
+        if self.location {
+            self.write_optional(out, self.file(record))?;
+            match record.line() {
+                Some(line) => write!(out, ":{} ", line)?,
+                None => write!(out, ":- ")?,
+            }
+        }
+
+        if out.len() > mark {
+            out.pop();
+            write!(out, ": ")?;
+        }
+        Ok(())
+    }
+
+    // The source file of `record`, shortened if configured.
+    fn file<'a>(&self, record: &Record<'a>) -> Option<&'a str> {
+        match record.file() {
+            Some(file) if self.short_paths => Some(short_path(file)),
+            file => file,
+        }
+    }
+
+    // The name of `thread` if it should be written instead of its ID.
+    fn thread_name<'a>(&self, thread: &'a Thread) -> Option<&'a str> {
+        match self.thread {
+            ThreadLabel::Id => None,
+            ThreadLabel::Name => thread.name(),
+        }
+    }
+
+    fn write_thread(&self, out: &mut Vec<u8>) -> io::Result<()> {
+        match self.thread_name(&thread::current()) {
+            Some(name) => self.write_sanitized(out, name),
+            None => write!(out, "{:x}", thread_id::get()),
+        }
+    }
+
+    // The escape sequence to color `field` with, if any.
+    fn style(
+        &self,
+        field: Field,
+        level: Level,
+        color: bool,
+    ) -> Option<&'static str> {
+        if !color {
+            return None;
+        }
+
+        match field {
+            Field::Level => Some(color::level_style(level)),
+            Field::Time | Field::Elapsed | Field::Wall
+                if self.color_details =>
+            {
+                Some(color::DIM)
+            }
+            Field::Target | Field::Module if self.color_details => {
+                Some(color::BOLD)
+            }
+            _ => None,
+        }
+    }
+
+    fn write_time(&self, out: &mut Vec<u8>, start: Instant) -> io::Result<()> {
+        match self.timestamp {
+            Timestamp::Uptime => write_uptime(out, start),
+            Timestamp::Utc(precision) => write_wall(
+                out,
+                DateTime::utc(SystemTime::now()),
+                precision.digits(),
+            ),
+            Timestamp::Local(precision) => write_wall(
+                out,
+                DateTime::local(SystemTime::now()),
+                precision.digits(),
+            ),
+        }
+    }
+}
+
+impl Default for Format {
+    fn default() -> Format {
+        Format::new()
+    }
+}
+
+fn write_wall(
+    out: &mut Vec<u8>,
+    time: DateTime,
+    digits: usize,
+) -> io::Result<()> {
+    let mut formatted = String::with_capacity(40);
+    let _ = time.write_rfc3339(&mut formatted, digits);
+    out.write_all(formatted.as_bytes())
+}
+
+// Strip everything before the last `src` directory of `path`.
+fn short_path(path: &str) -> &str {
+    for separator in &["/src/", "\\src\\"] {
+        if let Some(index) = path.rfind(separator) {
+            return &path[index + 1..];
+        }
+    }
+
+    path
+}
+
+pub(crate) fn write_json_string(
+    out: &mut Vec<u8>,
+    value: &str,
+) -> io::Result<()> {
+    write!(out, "\"")?;
+    let _ = fmt::Write::write_str(&mut JsonEscape(out), value);
+    write!(out, "\"")
+}
+
+fn write_json_optional(
+    out: &mut Vec<u8>,
+    value: Option<&str>,
+) -> io::Result<()> {
+    match value {
+        Some(value) => write_json_string(out, value),
+        None => write!(out, "null"),
+    }
+}
+
+pub(crate) fn write_logfmt_value(
+    out: &mut Vec<u8>,
+    value: &str,
+) -> io::Result<()> {
+    let quote = value.is_empty()
+        || value
+            .bytes()
+            .any(|byte| byte <= b' ' || byte == b'=' || byte == b'"');
+    if quote {
+        write_json_string(out, value)
+    } else {
+        out.write_all(value.as_bytes())
+    }
+}
+
+// Escapes everything written through it as the contents of a JSON string.
+pub(crate) struct JsonEscape<'a>(pub(crate) &'a mut Vec<u8>);
+
+impl<'a> fmt::Write for JsonEscape<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut start = 0;
+        for (index, byte) in s.bytes().enumerate() {
+            let escaped: &[u8] = match byte {
+                b'"' => b"\\\"",
+                b'\\' => b"\\\\",
+                b'\n' => b"\\n",
+                b'\r' => b"\\r",
+                b'\t' => b"\\t",
+                0x00..=0x1f => b"",
+                _ => continue,
+            };
+
+            self.0.extend_from_slice(&s.as_bytes()[start..index]);
+            if escaped.is_empty() {
+                let _ = write!(self.0, "\\u{:04x}", byte);
+            } else {
+                self.0.extend_from_slice(escaped);
+            }
+            start = index + 1;
+        }
+        self.0.extend_from_slice(&s.as_bytes()[start..]);
+
+        Ok(())
+    }
+}
+
+// Pad what was written to `out` since `mark` with spaces up to `width`
+// characters.
+fn pad(out: &mut Vec<u8>, mark: usize, align: Align, width: usize) {
+    let len = match str::from_utf8(&out[mark..]) {
+        Ok(written) => written.chars().count(),
+        Err(_) => out.len() - mark,
+    };
+    if len >= width {
+        return;
+    }
+
+    let padding = width - len;
+    match align {
+        Align::Left => out.extend((0..padding).map(|_| b' ')),
+        Align::Right => {
+            out.splice(mark..mark, (0..padding).map(|_| b' '));
+        }
+    }
+}
+
+// Write to `out` with `write`, wrapped in the `style` escape sequence if any.
+fn paint<F>(out: &mut Vec<u8>, style: Option<&str>, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut Vec<u8>) -> io::Result<()>,
+{
+    match style {
+        Some(style) => {
+            out.write_all(style.as_bytes())?;
+            write(out)?;
+            out.write_all(color::RESET.as_bytes())
+        }
+        None => write(out),
+    }
+}
+
+fn write_uptime(out: &mut Vec<u8>, start: Instant) -> io::Result<()> {
+    let now = start.elapsed();
+    let seconds = now.as_secs();
+    let hours = seconds / 3600;
+    let minutes = (seconds / 60) % 60;
+    let seconds = seconds % 60;
+    let miliseconds = now.subsec_millis();
+
+    write!(
+        out,
+        "{:02}:{:02}:{:02}.{:03}",
+        hours, minutes, seconds, miliseconds
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{short_path, Format, ThreadLabel};
+    use sanitize::Sanitize;
+
+    use log::{Level, Record};
+    use std::thread;
+    use std::time::Instant;
+
+    fn render(format: &Format, record: &Record, color: bool) -> String {
+        let mut out = Vec::new();
+        format
+            .write(&mut out, Instant::now(), record, color)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn template() {
+        let format = Format::new().template(
+            "[{level:<5}] {target} {module} {file}:{line:>4}: {message}"
+                .parse()
+                .unwrap(),
+        );
+        let line = render(
+            &format,
+            &Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .file(Some("src/main.rs"))
+                .line(Some(42))
+                .args(format_args!("hello"))
+                .build(),
+            false,
+        );
+
+        assert_eq!(line, "[INFO ] app - src/main.rs:  42: hello\n");
+    }
+
+    #[test]
+    fn columns() {
+        let write = |format: Format| {
+            render(
+                &format,
+                &Record::builder()
+                    .level(Level::Info)
+                    .target("app")
+                    .module_path(Some("app::db"))
+                    .file(Some("/home/me/app/src/db.rs"))
+                    .line(Some(42))
+                    .args(format_args!("hello"))
+                    .build(),
+                false,
+            )
+        };
+
+        let line = write(Format::new().target(true));
+        assert!(line.ends_with(") INFO   app: hello\n"));
+        let line = write(
+            Format::new()
+                .target(true)
+                .module(true)
+                .location(true)
+                .short_paths(true),
+        );
+        assert!(line.ends_with(") INFO   app app::db src/db.rs:42: hello\n"));
+        let line = write(Format::new().location(true));
+        assert!(line.ends_with(") INFO   /home/me/app/src/db.rs:42: hello\n"));
+    }
+
+    #[test]
+    fn short_paths() {
+        assert_eq!(short_path("src/main.rs"), "src/main.rs");
+        assert_eq!(
+            short_path("/cargo/registry/src/index/log-0.4.6/src/lib.rs"),
+            "src/lib.rs"
+        );
+        assert_eq!(short_path("C:\\app\\src\\main.rs"), "src\\main.rs");
+        assert_eq!(short_path("examples/demo.rs"), "examples/demo.rs");
+    }
+
+    #[test]
+    fn thread_name() {
+        let named = |format: Format| {
+            thread::Builder::new()
+                .name("pool 1".to_owned())
+                .spawn(move || {
+                    render(
+                        &format,
+                        &Record::builder()
+                            .level(Level::Info)
+                            .args(format_args!("hello"))
+                            .build(),
+                        false,
+                    )
+                })
+                .unwrap()
+                .join()
+                .unwrap()
+        };
+
+        let line = named(Format::new().thread(ThreadLabel::Name));
+        assert!(line.ends_with("] (pool 1) INFO   hello\n"));
+        let line = named(Format::logfmt().thread(ThreadLabel::Name));
+        assert!(line.contains(" thread=\"pool 1\" "));
+        let line = named(Format::json().thread(ThreadLabel::Name));
+        assert!(line.contains(r#","thread":"pool 1","#));
+        let line = named(Format::new());
+        assert!(!line.contains("pool 1"));
+    }
+
+    #[test]
+    fn sanitize() {
+        let format = Format::new()
+            .thread(ThreadLabel::Name)
+            .target(true)
+            .sanitize(Sanitize::Escape);
+        let line = thread::Builder::new()
+            .name("pool\n1".to_owned())
+            .spawn(move || {
+                render(
+                    &format,
+                    &Record::builder()
+                        .level(Level::Info)
+                        .target("app\n[forged]")
+                        .args(format_args!("a\nb \\n"))
+                        .build(),
+                    false,
+                )
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert_eq!(line.lines().count(), 1);
+        assert!(
+            line.ends_with("] (pool\\n1) INFO   app\\n[forged]: a\\nb \\\\n\n")
+        );
+    }
+
+    #[test]
+    fn color() {
+        let template = "{time} [{level:<5}] {target}: {message}";
+        let write = |format: Format| {
+            render(
+                &format,
+                &Record::builder()
+                    .level(Level::Warn)
+                    .target("app")
+                    .args(format_args!("hello"))
+                    .build(),
+                true,
+            )
+        };
+
+        let line = write(Format::new());
+        assert!(line.starts_with("[00:00:00.000] ("));
+        assert!(line.ends_with(") \x1b[33mWARN  \x1b[0m hello\n"));
+
+        let line = write(
+            Format::new()
+                .template(template.parse().unwrap())
+                .color_details(true),
+        );
+        assert!(line.starts_with("\x1b[2m00:00:00.000\x1b[0m "));
+        assert!(line
+            .ends_with(" [\x1b[33mWARN \x1b[0m] \x1b[1mapp\x1b[0m: hello\n"));
+
+        let line = write(Format::logfmt());
+        assert!(!line.contains('\x1b'));
+    }
+
+    #[test]
+    fn json() {
+        let line = render(
+            &Format::json(),
+            &Record::builder()
+                .level(Level::Warn)
+                .target("app")
+                .module_path(Some("app::db"))
+                .args(format_args!("a \"quoted\"\n\\ line\u{1}"))
+                .build(),
+            false,
+        );
+
+        assert!(line.starts_with("{\"ts\":\""));
+        assert!(line.contains(r#"","level":"WARN","thread":""#));
+        assert!(line.ends_with(concat!(
+            r#"","target":"app","module":"app::db","file":null,"line":null,"#,
+            r#""msg":"a \"quoted\"\n\\ line\u0001"}"#,
+            "\n"
+        )));
+    }
+
+    #[test]
+    fn logfmt() {
+        let format = Format::logfmt();
+        let write = |message: &str| {
+            render(
+                &format,
+                &Record::builder()
+                    .level(Level::Info)
+                    .target("app")
+                    .args(format_args!("{}", message))
+                    .build(),
+                false,
+            )
+        };
+
+        let line = write("connected");
+        assert!(line.starts_with("ts="));
+        assert!(line.contains(" level=info thread="));
+        assert!(line.ends_with(" target=app msg=connected\n"));
+        assert!(write("").ends_with(" msg=\"\"\n"));
+        assert!(write("a=\"b\"\nc")
+            .ends_with(concat!(r#" msg="a=\"b\"\nc""#, "\n")));
+    }
+}
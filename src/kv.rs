@@ -0,0 +1,217 @@
+// Rendering of structured key-value pairs attached to records. Without the
+// `kv` feature, records carry no key-value pairs and nothing is written.
+
+use log::Record;
+use std::io;
+
+#[cfg(feature = "kv")]
+use format::{write_json_string, write_logfmt_value, JsonEscape};
+#[cfg(feature = "kv")]
+use log::kv::{self, Key, Value, VisitSource};
+#[cfg(feature = "kv")]
+use std::fmt;
+#[cfg(feature = "kv")]
+use std::io::Write;
+
+// The fields written by the JSON and logfmt formats themselves. Keys with these
+// names are prefixed with `kv.` in those formats, so they can't be mistaken for
+// or override the record's own fields. Keys already starting with `kv.` are
+// prefixed again, so `level` and `kv.level` don't clash after prefixing.
+#[cfg(feature = "kv")]
+const RESERVED: &[&str] = &[
+    "ts", "level", "thread", "target", "module", "file", "line", "msg",
+];
+
+#[cfg(feature = "kv")]
+fn reserved(key: &Key) -> bool {
+    RESERVED.contains(&key.as_str()) || key.as_str().starts_with("kv.")
+}
+
+// Write each pair as ` key=value`, using logfmt quoting.
+#[cfg(feature = "kv")]
+pub fn write_logfmt(out: &mut Vec<u8>, record: &Record) -> io::Result<()> {
+    write_pairs(out, record, false)
+}
+
+// Like `write_logfmt()`, but prefixing reserved keys, for logfmt lines.
+#[cfg(feature = "kv")]
+pub fn write_logfmt_fields(
+    out: &mut Vec<u8>,
+    record: &Record,
+) -> io::Result<()> {
+    write_pairs(out, record, true)
+}
+
+#[cfg(feature = "kv")]
+fn write_pairs(
+    out: &mut Vec<u8>,
+    record: &Record,
+    prefix_reserved: bool,
+) -> io::Result<()> {
+    visit(record, |key, value| {
+        write!(out, " ")?;
+        if prefix_reserved && reserved(&key) {
+            write_logfmt_value(out, &format!("kv.{}", key))?;
+        } else {
+            write_logfmt_value(out, key.as_str())?;
+        }
+        write!(out, "=")?;
+        match value.to_borrowed_str() {
+            Some(value) => write_logfmt_value(out, value),
+            None => write_logfmt_value(out, &value.to_string()),
+        }
+    })
+}
+
+// Write each pair as a `,"key":value` JSON member, prefixing reserved keys.
+// Booleans and numbers are written as such, and anything else as a string.
+#[cfg(feature = "kv")]
+pub fn write_json(out: &mut Vec<u8>, record: &Record) -> io::Result<()> {
+    visit(record, |key, value| {
+        write!(out, ",")?;
+        if reserved(&key) {
+            write_json_string(out, &format!("kv.{}", key))?;
+        } else {
+            write_json_string(out, key.as_str())?;
+        }
+        write!(out, ":")?;
+        if let Some(value) = value.to_bool() {
+            write!(out, "{}", value)
+        } else if let Some(value) = value.to_i64() {
+            write!(out, "{}", value)
+        } else if let Some(value) = value.to_u64() {
+            write!(out, "{}", value)
+        } else if let Some(value) =
+            value.to_f64().filter(|value| value.is_finite())
+        {
+            write!(out, "{}", value)
+        } else {
+            write!(out, "\"")?;
+            let _ = fmt::write(&mut JsonEscape(out), format_args!("{}", value));
+            write!(out, "\"")
+        }
+    })
+}
+
+#[cfg(feature = "kv")]
+fn visit<F>(record: &Record, write: F) -> io::Result<()>
+where
+    F: FnMut(Key, Value) -> io::Result<()>,
+{
+    struct Visitor<F> {
+        write: F,
+        result: io::Result<()>,
+    }
+
+    impl<'kvs, F> VisitSource<'kvs> for Visitor<F>
+    where
+        F: FnMut(Key, Value) -> io::Result<()>,
+    {
+        fn visit_pair(
+            &mut self,
+            key: Key<'kvs>,
+            value: Value<'kvs>,
+        ) -> Result<(), kv::Error> {
+            self.result = (self.write)(key, value);
+            self.result
+                .as_ref()
+                .map_err(|_| kv::Error::msg("failed to write key-value pair"))
+                .map(|_| ())
+        }
+    }
+
+    let mut visitor = Visitor {
+        write,
+        result: Ok(()),
+    };
+    let _ = record.key_values().visit(&mut visitor);
+
+    visitor.result
+}
+
+#[cfg(not(feature = "kv"))]
+pub fn write_logfmt(_: &mut Vec<u8>, _: &Record) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(not(feature = "kv"))]
+pub fn write_logfmt_fields(_: &mut Vec<u8>, _: &Record) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(not(feature = "kv"))]
+pub fn write_json(_: &mut Vec<u8>, _: &Record) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(all(test, feature = "kv"))]
+mod tests {
+    use super::{write_json, write_logfmt, write_logfmt_fields};
+
+    use log::kv::{Key, Value};
+    use log::{Level, Record};
+    use std::str;
+
+    #[test]
+    fn pairs() {
+        let pairs: &[(Key, Value)] = &[
+            (Key::from("user"), Value::from("carol s")),
+            (Key::from("id"), Value::from(42)),
+            (Key::from("ok"), Value::from(true)),
+        ];
+        let record = Record::builder()
+            .level(Level::Info)
+            .key_values(&pairs)
+            .args(format_args!("test"))
+            .build();
+
+        let mut out = Vec::new();
+        write_logfmt(&mut out, &record).unwrap();
+        assert_eq!(
+            str::from_utf8(&out).unwrap(),
+            r#" user="carol s" id=42 ok=true"#
+        );
+
+        out.clear();
+        write_json(&mut out, &record).unwrap();
+        assert_eq!(
+            str::from_utf8(&out).unwrap(),
+            r#","user":"carol s","id":42,"ok":true"#
+        );
+    }
+
+    #[test]
+    fn reserved() {
+        let pairs: &[(Key, Value)] = &[
+            (Key::from("level"), Value::from("forged")),
+            (Key::from("msg"), Value::from(1)),
+            (Key::from("kv.level"), Value::from("kept")),
+        ];
+        let record = Record::builder()
+            .level(Level::Info)
+            .key_values(&pairs)
+            .args(format_args!("test"))
+            .build();
+
+        let mut out = Vec::new();
+        write_logfmt(&mut out, &record).unwrap();
+        assert_eq!(
+            str::from_utf8(&out).unwrap(),
+            " level=forged msg=1 kv.level=kept"
+        );
+
+        out.clear();
+        write_logfmt_fields(&mut out, &record).unwrap();
+        assert_eq!(
+            str::from_utf8(&out).unwrap(),
+            " kv.level=forged kv.msg=1 kv.kv.level=kept"
+        );
+
+        out.clear();
+        write_json(&mut out, &record).unwrap();
+        assert_eq!(
+            str::from_utf8(&out).unwrap(),
+            r#","kv.level":"forged","kv.msg":1,"kv.kv.level":"kept""#
+        );
+    }
+}
@@ -18,7 +18,8 @@
 //! ```
 //!
 //! Or use [`log_to_stderr()`](fn.log_to_stderr.html) if simply logging to
-//! `stderr`:
+//! `stderr` (or [`log_to_console()`](fn.log_to_console.html) to send less
+//! severe messages to `stdout`):
 //!
 //! ```rust
 //! # extern crate log;
@@ -46,6 +47,42 @@
 //! # }
 //! ```
 //!
+//! Everything else can be configured at once with a
+//! [`Builder`](struct.Builder.html):
+//!
+//! ```rust
+//! # extern crate log;
+//! # extern crate simple_logging;
+//! use log::LevelFilter;
+//! use simple_logging::{Builder, Format};
+//! use std::io;
+//!
+//! # fn main() {
+//! Builder::new()
+//!     .sink(io::sink())
+//!     .level(LevelFilter::Debug)
+//!     .format(Format::logfmt())
+//!     .init()
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! [`Builder::build()`](struct.Builder.html#method.build) creates an
+//! independent [`SimpleLogger`](struct.SimpleLogger.html) instead, which
+//! leaves the global logger untouched.
+//!
+//! The `log_to*()` functions panic if a different logger has already been set
+//! for the `log` facade. Libraries and plugins that should keep working
+//! regardless can use [`try_log_to()`](fn.try_log_to.html),
+//! [`try_log_to_file()`](fn.try_log_to_file.html) or
+//! [`try_log_to_stderr()`](fn.try_log_to_stderr.html) instead.
+//!
+//! [`log_to_file()`](fn.log_to_file.html) truncates the file.
+//! [`log_to_file_append()`](fn.log_to_file_append.html) appends to it instead.
+//! Log files can also be rotated once they reach a certain size by using a
+//! [`RotatingFile`](struct.RotatingFile.html) as the sink, or at wall-clock
+//! boundaries with [`log_to_timed_file()`](fn.log_to_timed_file.html).
+//!
 //! # Log format
 //!
 //! Each and every log message obeys the following fixed and easily-parsable
@@ -58,19 +95,74 @@
 //! Where `<hh>` denotes hours zero-padded to at least two digits, `<mm>`
 //! denotes minutes zero-padded to two digits, `<ss>` denotes seconds
 //! zero-padded to two digits and `<SSS>` denotes miliseconds zero-padded to
-//! three digits. `<thread-id>` is an implementation-specific alphanumeric ID.
-//! `<level>` is the log level as defined by `log::LogLevel` and padded right
-//! with spaces. `<message>` is the log message. Note that `<message>` is
-//! written to the log as-is, including any embedded newlines.
+//! three digits, all counted from the time the logger was configured. The
+//! time can be replaced by a wall-clock RFC 3339 timestamp through
+//! [`set_format()`](fn.set_format.html), which can also replace the whole
+//! line with a custom [`Template`](struct.Template.html). `<thread-id>` is an
+//! implementation-specific alphanumeric ID, or the thread name if configured
+//! with [`ThreadLabel::Name`](enum.ThreadLabel.html). `<level>` is the log
+//! level as defined by `log::LogLevel` and padded right with spaces.
+//! `<message>` is the log message, followed by its structured key-value pairs
+//! as ` key=value` if the `kv` feature is enabled. Columns with the target,
+//! module path and source location of the message can be added before it (see
+//! [`Format::location()`](struct.Format.html#method.location)). When logging
+//! to a terminal with [`log_to_stderr()`](fn.log_to_stderr.html), the level
+//! is colored with ANSI escape sequences (see
+//! [`ColorChoice`](enum.ColorChoice.html)). Note that by default `<message>`
+//! is written to the log as-is, including any embedded newlines. To guarantee
+//! that each message occupies a single line, escape them with
+//! [`Sanitize::Escape`](enum.Sanitize.html).
+//!
+//! # Filtering
+//!
+//! Besides a global maximum level, messages can be filtered per target with a
+//! [`Filter`](struct.Filter.html). Each directive applies to a target and all
+//! of its child modules, with the longest matching target taking precedence:
+//!
+//! ```rust
+//! # extern crate simple_logging;
+//! use simple_logging::Filter;
+//!
+//! # fn main() {
+//! let filter: Filter = "hyper=warn,my_app::db=trace,info".parse().unwrap();
+//! simple_logging::log_to_stderr(filter);
+//! # }
+//! ```
+//!
+//! # Reconfiguration
+//!
+//! Calling any of the `log_to*()` functions again replaces the whole
+//! configuration and restarts the time counted by uptime timestamps. To change
+//! only part of it, use [`set_level()`](fn.set_level.html),
+//! [`set_filter()`](fn.set_filter.html), [`set_format()`](fn.set_format.html),
+//! [`set_sink()`](fn.set_sink.html) or [`set_outputs()`](fn.set_outputs.html)
+//! instead. These can be called at any time from any thread.
 //!
 //! # Errors
 //!
-//! Any errors returned by the sink when writing are ignored.
+//! By default, any errors returned by the sink when writing are counted (see
+//! [`failed_writes()`](fn.failed_writes.html)) and otherwise ignored. An
+//! [`ErrorPolicy`](struct.ErrorPolicy.html) can be set to be notified of
+//! errors or to switch to a fallback sink.
+//!
+//! # Testing
+//!
+//! A [`Capture`](struct.Capture.html) sink keeps log lines in memory and
+//! parses them back, to check what was logged in tests.
 //!
 //! # Performance
 //!
 //! The logger relies on a global `Mutex` to serialize access to the user
-//! supplied sink.
+//! supplied sink. To keep slow sinks from stalling the logging threads, wrap
+//! them in an [`AsyncSink`](struct.AsyncSink.html), which writes messages from
+//! a background thread. Messages can also be buffered and written in batches
+//! with [`set_flush_policy()`](fn.set_flush_policy.html).
+//!
+//! # Multiple outputs
+//!
+//! [`log_to_outputs()`](fn.log_to_outputs.html) sends messages to several
+//! sinks at once, each with its own filter and format. See
+//! [`Output`](struct.Output.html).
 
 #[macro_use]
 extern crate lazy_static;
@@ -81,83 +173,409 @@ extern crate thread_id;
 #[cfg(test)]
 #[macro_use]
 extern crate log;
-#[cfg(test)]
+#[cfg(unix)]
+extern crate libc;
+#[cfg(any(test, feature = "regex"))]
 extern crate regex;
 
 // TODO: include the changelog as a module when
 // https://github.com/rust-lang/rust/issues/44732 stabilises
 
-use log::{LevelFilter, Log, Metadata, Record};
-use std::fs::File;
+mod async_sink;
+mod builder;
+mod capture;
+mod color;
+mod error;
+mod error_policy;
+mod filter;
+mod flush;
+mod format;
+mod kv;
+mod output;
+mod rotate;
+mod sanitize;
+mod template;
+mod time;
+
+pub use async_sink::{AsyncSink, AsyncStats, OverflowPolicy};
+pub use builder::Builder;
+pub use capture::{Capture, CapturedRecord};
+pub use color::ColorChoice;
+pub use error::Error;
+pub use error_policy::ErrorPolicy;
+pub use filter::{Filter, ParseFilterError};
+pub use flush::FlushPolicy;
+pub use format::{Format, Precision, ThreadLabel, Timestamp};
+pub use output::Output;
+pub use rotate::{Period, RotatingFile, TimedRotatingFile};
+pub use sanitize::Sanitize;
+pub use template::{ParseTemplateError, Template};
+
+use error_policy::{Errors, Failure};
+use flush::Timer;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fmt;
+use std::fs::{File, OpenOptions};
 use std::io;
 use std::io::Write;
+use std::mem;
 use std::path::Path;
-use std::sync::Mutex;
-use std::time::Instant;
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 lazy_static! {
-    static ref LOGGER: SimpleLogger = SimpleLogger {
-        inner: Mutex::new(None),
-    };
+    static ref LOGGER: SimpleLogger = SimpleLogger::off();
 }
 
-struct SimpleLogger {
-    inner: Mutex<Option<SimpleLoggerInner>>,
+/// A logger that is independent of the global one, with its own sinks, filter,
+/// format and clock.
+///
+/// The `log_to*()` functions configure a global `SimpleLogger` and install it
+/// for the [`log`](https://crates.io/crates/log) facade. Separate instances can
+/// be created to be driven directly through the
+/// [`Log`](https://docs.rs/log/0.4/log/trait.Log.html) trait, composed into
+/// other loggers or handed to libraries taking a `&dyn Log`, without touching
+/// any global state. Use a [`Builder`](struct.Builder.html) to create one
+/// with more settings.
+///
+/// Buffered messages are written out when the logger is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::{Level, LevelFilter, Log, Record};
+/// use simple_logging::SimpleLogger;
+/// use std::io;
+///
+/// # fn main() {
+/// let logger = SimpleLogger::new(io::stderr(), LevelFilter::Info);
+/// logger.log(
+///     &Record::builder()
+///         .level(Level::Info)
+///         .args(format_args!("hello"))
+///         .build(),
+/// );
+/// # }
+/// ```
+pub struct SimpleLogger {
+    inner: Arc<Mutex<SimpleLoggerInner>>,
+    // Flushes buffered messages with `FlushPolicy::Interval`
+    timer: Mutex<Option<Timer>>,
 }
 
 impl SimpleLogger {
-    // Set this `SimpleLogger`'s sink and reset the start time.
-    fn renew<T: Write + Send + 'static>(&self, sink: T) {
-        *self.inner.lock().unwrap() = Some(SimpleLoggerInner {
-            start: Instant::now(),
-            sink: Box::new(sink),
-        });
+    /// Create a logger writing messages allowed by `filter` to `sink`.
+    pub fn new<T: Write + Send + 'static, F: Into<Filter>>(
+        sink: T,
+        filter: F,
+    ) -> SimpleLogger {
+        let logger = SimpleLogger::off();
+        logger.renew(vec![Output::new(sink)], filter.into());
+        logger
+    }
+
+    /// The maximum level of messages this logger writes, as expected by
+    /// [`log::set_max_level()`](https://docs.rs/log/0.4/log/fn.set_max_level.html).
+    pub fn max_level(&self) -> LevelFilter {
+        self.inner.lock().unwrap().max_level()
+    }
+
+    /// The number of writes to the sinks of this logger that have failed.
+    pub fn failed_writes(&self) -> u64 {
+        self.inner.lock().unwrap().errors.failed()
+    }
+
+    // Create a logger without outputs, which discards all messages.
+    fn off() -> SimpleLogger {
+        SimpleLogger {
+            inner: Arc::new(Mutex::new(SimpleLoggerInner {
+                start: Instant::now(),
+                outputs: Vec::new(),
+                filter: Filter::new(LevelFilter::Off),
+                format: Format::new(),
+                flush_policy: FlushPolicy::Unbuffered,
+                buffer: Vec::new(),
+                errors: Errors::new(),
+            })),
+            timer: Mutex::new(None),
+        }
+    }
+
+    // Set this `SimpleLogger`'s outputs and filter, and reset the start time.
+    // Returns the maximum level that can be logged.
+    fn renew(&self, outputs: Vec<Output>, filter: Filter) -> LevelFilter {
+        let (max_level, old, failures) = {
+            let mut inner = self.inner.lock().unwrap();
+            let (old, failures) = inner.replace_outputs(outputs);
+            inner.start = Instant::now();
+            inner.filter = filter;
+            (inner.max_level(), old, failures)
+        };
+        // Dropped outside the lock, as it may take a while
+        drop(old);
+        report(failures);
+
+        max_level
+    }
+
+    // Replace the outputs, keeping the filter and start time. Returns the
+    // maximum level that can be logged.
+    fn set_outputs(&self, outputs: Vec<Output>) -> LevelFilter {
+        let (max_level, old, failures) = {
+            let mut inner = self.inner.lock().unwrap();
+            let (old, failures) = inner.replace_outputs(outputs);
+            (inner.max_level(), old, failures)
+        };
+        drop(old);
+        report(failures);
+
+        max_level
+    }
+
+    fn set_filter(&self, filter: Filter) -> LevelFilter {
+        let mut inner = self.inner.lock().unwrap();
+        inner.filter = filter;
+        inner.max_level()
+    }
+
+    // Set the default level of the filter, keeping its directives.
+    fn set_level(&self, level: LevelFilter) -> LevelFilter {
+        let mut inner = self.inner.lock().unwrap();
+        let filter = mem::replace(&mut inner.filter, Filter::new(level));
+        inner.filter = filter.default_level(level);
+        inner.max_level()
+    }
+
+    fn set_format(&self, format: Format) {
+        self.inner.lock().unwrap().format = format;
+    }
+
+    // Flush and drop the outputs. Messages logged afterwards are discarded.
+    fn shutdown(&self) {
+        let (outputs, failures) = {
+            let mut inner = self.inner.lock().unwrap();
+            let failures = inner.flush();
+            (mem::take(&mut inner.outputs), failures)
+        };
+        // Dropped outside the lock, as it may take a while
+        drop(outputs);
+        report(failures);
+    }
+
+    fn set_flush_policy(&self, policy: FlushPolicy) {
+        let failures = {
+            let mut inner = self.inner.lock().unwrap();
+            let failures = inner.flush();
+            inner.flush_policy = policy;
+            for output in &mut inner.outputs {
+                output.set_flush_policy(policy);
+            }
+            failures
+        };
+        report(failures);
+        // A zero interval flushes every message, so no timer is needed
+        *self.timer.lock().unwrap() = match policy {
+            FlushPolicy::Interval(interval)
+                if interval > Duration::new(0, 0) =>
+            {
+                Some(self.start_timer(interval))
+            }
+            _ => None,
+        };
+    }
+
+    // Start a thread flushing buffered messages every `interval`. It holds
+    // no strong reference, so it stops once the logger is dropped.
+    fn start_timer(&self, interval: Duration) -> Timer {
+        let inner = Arc::downgrade(&self.inner);
+        Timer::start(interval, move || match inner.upgrade() {
+            Some(inner) => {
+                let failures = inner.lock().unwrap().flush_pending();
+                report(failures);
+                true
+            }
+            None => false,
+        })
+    }
+
+    fn set_error_policy(&self, policy: ErrorPolicy) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.errors.set_policy(policy);
+        for output in &mut inner.outputs {
+            output.reset_failures();
+        }
+    }
+
+    // Write a message marking the start of a new session to every output,
+    // regardless of filters.
+    fn mark_session(&self) {
+        let failures = self.inner.lock().unwrap().log(
+            &Record::builder()
+                .level(Level::Info)
+                .target(module_path!())
+                .args(format_args!(
+                    "--- session started (pid {}) ---",
+                    process::id()
+                ))
+                .build(),
+            false,
+        );
+        report(failures);
     }
 }
 
 impl Log for SimpleLogger {
-    fn enabled(&self, _: &Metadata) -> bool {
-        true
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner.filter.enabled(metadata)
+            && inner.outputs.iter().any(|output| output.enabled(metadata))
     }
 
     fn log(&self, record: &Record) {
-        if !self.enabled(record.metadata()) {
-            return;
-        }
+        let failures = {
+            let mut inner = self.inner.lock().unwrap();
+            if !inner.filter.matches(record) {
+                return;
+            }
+            inner.log(record, true)
+        };
+        report(failures);
+    }
 
-        if let Some(ref mut inner) = *self.inner.lock().unwrap() {
-            inner.log(record);
-        }
+    fn flush(&self) {
+        let failures = self.inner.lock().unwrap().flush();
+        report(failures);
     }
+}
+
+impl Drop for SimpleLogger {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
 
-    fn flush(&self) {}
+impl fmt::Debug for SimpleLogger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        f.debug_struct("SimpleLogger")
+            .field("outputs", &inner.outputs)
+            .field("filter", &inner.filter)
+            .field("format", &inner.format)
+            .finish()
+    }
+}
+
+// Report write failures. Must be called with the logger unlocked.
+fn report(failures: Vec<Failure>) {
+    for failure in failures {
+        failure.report();
+    }
 }
 
 struct SimpleLoggerInner {
     start: Instant,
-    sink: Box<Write + Send>,
+    outputs: Vec<Output>,
+    filter: Filter,
+    format: Format,
+    flush_policy: FlushPolicy,
+    // Reused between messages so each one is written with a single call
+    buffer: Vec<u8>,
+    errors: Errors,
 }
 
 impl SimpleLoggerInner {
-    fn log(&mut self, record: &Record) {
-        let now = self.start.elapsed();
-        let seconds = now.as_secs();
-        let hours = seconds / 3600;
-        let minutes = (seconds / 60) % 60;
-        let seconds = seconds % 60;
-        let miliseconds = now.subsec_nanos() / 1_000_000;
-
-        let _ = write!(
-            self.sink,
-            "[{:02}:{:02}:{:02}.{:03}] ({:x}) {:6} {}\n",
-            hours,
-            minutes,
-            seconds,
-            miliseconds,
-            thread_id::get(),
-            record.level(),
-            record.args()
-        );
+    // The maximum level allowed by both the filter and any of the outputs.
+    fn max_level(&self) -> LevelFilter {
+        let outputs = self
+            .outputs
+            .iter()
+            .map(Output::max_level)
+            .max()
+            .unwrap_or(LevelFilter::Off);
+        outputs.min(self.filter.max_level())
+    }
+
+    // Flush the current outputs and replace them with `outputs`, returning
+    // the old ones.
+    fn replace_outputs(
+        &mut self,
+        mut outputs: Vec<Output>,
+    ) -> (Vec<Output>, Vec<Failure>) {
+        let failures = self.flush();
+        for output in &mut outputs {
+            output.set_flush_policy(self.flush_policy);
+            output.resolve_color();
+        }
+
+        (mem::replace(&mut self.outputs, outputs), failures)
+    }
+
+    // Write `record` to every output, skipping those whose filter rejects it
+    // if `filtered` is `true`.
+    fn log(&mut self, record: &Record, filtered: bool) -> Vec<Failure> {
+        let mut failures = Vec::new();
+        for output in &mut self.outputs {
+            if filtered && !output.matches(record) {
+                continue;
+            }
+            self.buffer.clear();
+            let _ = output.format_or(&self.format).write(
+                &mut self.buffer,
+                self.start,
+                record,
+                output.colored(),
+            );
+            failures.extend(output.write(
+                &mut self.errors,
+                &self.buffer,
+                record.level(),
+            ));
+        }
+
+        failures
+    }
+
+    fn flush(&mut self) -> Vec<Failure> {
+        let errors = &mut self.errors;
+        self.outputs
+            .iter_mut()
+            .filter_map(|output| output.flush(errors))
+            .collect()
+    }
+
+    // Flush the outputs that have buffered messages.
+    fn flush_pending(&mut self) -> Vec<Failure> {
+        let errors = &mut self.errors;
+        self.outputs
+            .iter_mut()
+            .filter_map(|output| output.flush_pending(errors))
+            .collect()
+    }
+}
+
+/// A guard that flushes and shuts down the logger when dropped.
+///
+/// Returned by [`log_to_with_guard()`](fn.log_to_with_guard.html) and
+/// [`Builder::init_with_guard()`](struct.Builder.html#method.init_with_guard).
+/// Keep it alive until the end of `main` to ensure any buffered or queued
+/// messages reach the sink before the process exits. Dropping the guard writes
+/// out any buffered messages, flushes the sink and then drops it. If the sink
+/// is an [`AsyncSink`](struct.AsyncSink.html), this waits for the writer
+/// thread to write all queued messages and exit. Messages logged after the
+/// guard is dropped are discarded.
+#[must_use = "the logger is shut down as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct FlushGuard {
+    _private: (),
+}
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        LOGGER.shutdown();
     }
 }
 
@@ -174,12 +592,116 @@ impl SimpleLoggerInner {
 /// simple_logging::log_to_file("test.log", LevelFilter::Info);
 /// # }
 /// ```
-pub fn log_to_file<T: AsRef<Path>>(
+pub fn log_to_file<T: AsRef<Path>, F: Into<Filter>>(
     path: T,
-    max_log_level: LevelFilter,
+    filter: F,
 ) -> io::Result<()> {
-    let file = File::create(path)?;
-    log_to(file, max_log_level);
+    let file = open_unchanged(path)?;
+    expect_register();
+    file.set_len(0)?;
+    log_to(file, filter);
+
+    Ok(())
+}
+
+/// Like [`log_to_file()`](fn.log_to_file.html), but returns an error instead
+/// of panicking if a different logger has already been set.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::Error;
+///
+/// # fn main() {
+/// match simple_logging::try_log_to_file("test.log", LevelFilter::Info) {
+///     Ok(()) => {}
+///     Err(Error::LoggerAlreadySet) => {} // Keep the existing logger
+///     Err(Error::Io(err)) => eprintln!("failed to open log file: {}", err),
+/// }
+/// # }
+/// ```
+pub fn try_log_to_file<T: AsRef<Path>, F: Into<Filter>>(
+    path: T,
+    filter: F,
+) -> Result<(), Error> {
+    let file = open_unchanged(path)?;
+    register()?;
+    file.set_len(0)?;
+    try_log_to(file, filter)
+}
+
+/// Configure the [`log`](https://crates.io/crates/log) facade to append to a
+/// file, keeping any previous contents.
+///
+/// If `session_marker` is `true`, an `INFO` message containing the process ID
+/// is written first to mark the start of the new session. It is written even
+/// if `INFO` messages are filtered out.
+///
+/// The file is opened in append mode and each message is written with a
+/// single `write` call, so messages from several processes appending to the
+/// same file do not interleave within a line.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+///
+/// # fn main() {
+/// simple_logging::log_to_file_append("test.log", LevelFilter::Info, true);
+/// # }
+/// ```
+pub fn log_to_file_append<T: AsRef<Path>, F: Into<Filter>>(
+    path: T,
+    filter: F,
+    session_marker: bool,
+) -> io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    log_to(file, filter);
+    if session_marker {
+        LOGGER.mark_session();
+    }
+
+    Ok(())
+}
+
+/// Configure the [`log`](https://crates.io/crates/log) facade to log to a
+/// series of files, starting a new one every `period`.
+///
+/// `pattern` is expanded with the start time of each period as described in
+/// [`TimedRotatingFile`](struct.TimedRotatingFile.html). Periods start at UTC
+/// boundaries, so in UTC+9 a daily file starts at 09:00 local time. To follow
+/// the local wall clock instead, use
+/// [`TimedRotatingFile::local()`](struct.TimedRotatingFile.html#method.local)
+/// as the sink.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::Period;
+///
+/// # fn main() {
+/// simple_logging::log_to_timed_file(
+///     "test-%Y-%m-%d.log",
+///     Period::Daily,
+///     LevelFilter::Info,
+/// );
+/// # }
+/// ```
+pub fn log_to_timed_file<T: Into<String>, F: Into<Filter>>(
+    pattern: T,
+    period: Period,
+    filter: F,
+) -> io::Result<()> {
+    let file = TimedRotatingFile::new(pattern, period)?;
+    log_to(file, filter);
 
     Ok(())
 }
@@ -187,6 +709,9 @@ pub fn log_to_file<T: AsRef<Path>>(
 /// Configure the [`log`](https://crates.io/crates/log) facade to log to
 /// `stderr`.
 ///
+/// Levels are colored if `stderr` is a terminal, as described in
+/// [`ColorChoice`](enum.ColorChoice.html).
+///
 /// # Examples
 ///
 /// ```rust
@@ -198,13 +723,224 @@ pub fn log_to_file<T: AsRef<Path>>(
 /// simple_logging::log_to_stderr(LevelFilter::Info);
 /// # }
 /// ```
-pub fn log_to_stderr(max_log_level: LevelFilter) {
-    log_to(io::stderr(), max_log_level);
+pub fn log_to_stderr<F: Into<Filter>>(filter: F) {
+    install(vec![Output::stderr()], filter.into());
+}
+
+/// Like [`log_to_stderr()`](fn.log_to_stderr.html), but returns an error
+/// instead of panicking if a different logger has already been set.
+pub fn try_log_to_stderr<F: Into<Filter>>(filter: F) -> Result<(), Error> {
+    try_install(vec![Output::stderr()], filter.into())
+}
+
+/// Configure the [`log`](https://crates.io/crates/log) facade to log to the
+/// console, writing messages at `stderr_level` or more severe to `stderr` and
+/// everything else to `stdout`.
+///
+/// # Examples
+///
+/// Send `Warn` and `Error` messages to `stderr`, and `Info` messages to
+/// `stdout`:
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::{Level, LevelFilter};
+///
+/// # fn main() {
+/// simple_logging::log_to_console(LevelFilter::Info, Level::Warn);
+/// # }
+/// ```
+pub fn log_to_console<F: Into<Filter>>(filter: F, stderr_level: Level) {
+    install(
+        vec![
+            Output::stdout().less_severe_than(stderr_level),
+            Output::stderr().filter(stderr_level.to_level_filter()),
+        ],
+        filter.into(),
+    );
+}
+
+/// Configure the [`log`](https://crates.io/crates/log) facade to log to
+/// `stderr`, reading the filter from the `RUST_LOG` environment variable.
+///
+/// `RUST_LOG` uses the syntax described in
+/// [`Filter`](struct.Filter.html#impl-FromStr). If it is not set, `default` is
+/// used instead.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+///
+/// # fn main() {
+/// simple_logging::log_to_stderr_from_env(LevelFilter::Info).unwrap();
+/// # }
+/// ```
+pub fn log_to_stderr_from_env<F: Into<Filter>>(
+    default: F,
+) -> Result<(), ParseFilterError> {
+    log_to_stderr(Filter::from_env("RUST_LOG", default)?);
+
+    Ok(())
+}
+
+/// Set the default level of the logger's filter, keeping any per-target
+/// directives.
+///
+/// Unlike the `log_to*()` functions, this doesn't reset the time counted by
+/// uptime timestamps.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+///
+/// # fn main() {
+/// simple_logging::log_to_stderr(LevelFilter::Info);
+/// // ...
+/// simple_logging::set_level(LevelFilter::Debug);
+/// # }
+/// ```
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(LOGGER.set_level(level));
+}
+
+/// Replace the logger's filter.
+///
+/// Unlike the `log_to*()` functions, this doesn't reset the time counted by
+/// uptime timestamps.
+pub fn set_filter<F: Into<Filter>>(filter: F) {
+    log::set_max_level(LOGGER.set_filter(filter.into()));
+}
+
+/// Replace the sink, keeping the filter and all other settings.
+///
+/// Any buffered messages are written to the old sink before it is dropped.
+/// Unlike the `log_to*()` functions, this doesn't reset the time counted by
+/// uptime timestamps.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use std::fs::File;
+/// use std::io;
+///
+/// # fn main() {
+/// simple_logging::log_to_stderr(LevelFilter::Info);
+/// // ...
+/// simple_logging::set_sink(File::create("test.log").unwrap());
+/// # }
+/// ```
+pub fn set_sink<T: Write + Send + 'static>(sink: T) {
+    set_outputs(vec![Output::new(sink)]);
+}
+
+/// Replace the outputs, keeping the filter and all other settings.
+///
+/// Any buffered messages are written to the old outputs before they are
+/// dropped. Unlike [`log_to_outputs()`](fn.log_to_outputs.html), this doesn't
+/// reset the time counted by uptime timestamps.
+pub fn set_outputs<I: IntoIterator<Item = Output>>(outputs: I) {
+    log::set_max_level(LOGGER.set_outputs(outputs.into_iter().collect()));
+}
+
+/// Set the format of log lines.
+///
+/// The format is kept when the logger is reconfigured with any of the
+/// `log_to*()` functions.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::{Format, Precision, Timestamp};
+///
+/// # fn main() {
+/// simple_logging::set_format(
+///     Format::new().timestamp(Timestamp::Local(Precision::Micros)),
+/// );
+/// simple_logging::log_to_stderr(LevelFilter::Info);
+/// # }
+/// ```
+pub fn set_format(format: Format) {
+    LOGGER.set_format(format);
+}
+
+/// Set when messages are written to the sink and the sink flushed.
+///
+/// Any buffered messages are written out before switching policies. The
+/// policy is kept when the logger is reconfigured with any of the `log_to*()`
+/// functions. See [`FlushPolicy`](enum.FlushPolicy.html) for details.
+pub fn set_flush_policy(policy: FlushPolicy) {
+    LOGGER.set_flush_policy(policy);
+}
+
+/// Set what to do when writing to the sink fails.
+///
+/// The policy is kept when the logger is reconfigured with any of the
+/// `log_to*()` functions. See [`ErrorPolicy`](struct.ErrorPolicy.html) for
+/// details.
+pub fn set_error_policy(policy: ErrorPolicy) {
+    LOGGER.set_error_policy(policy);
+}
+
+/// The number of writes to the sink that have failed since the program
+/// started.
+pub fn failed_writes() -> u64 {
+    LOGGER.failed_writes()
+}
+
+/// Configure the [`log`](https://crates.io/crates/log) facade to log to a
+/// custom sink, returning a guard that flushes and shuts down the logger when
+/// dropped.
+///
+/// See [`FlushGuard`](struct.FlushGuard.html) for details.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::{AsyncSink, OverflowPolicy};
+/// use std::io;
+///
+/// fn main() {
+///     let sink = AsyncSink::new(io::stderr(), 1024, OverflowPolicy::Block);
+///     let _guard = simple_logging::log_to_with_guard(sink, LevelFilter::Info);
+///     // Queued messages are written before `main` returns
+/// }
+/// ```
+pub fn log_to_with_guard<T: Write + Send + 'static, F: Into<Filter>>(
+    sink: T,
+    filter: F,
+) -> FlushGuard {
+    log_to(sink, filter);
+
+    FlushGuard { _private: () }
 }
 
 /// Configure the [`log`](https://crates.io/crates/log) facade to log to a
 /// custom sink.
 ///
+/// `filter` is either a plain `LevelFilter` or a
+/// [`Filter`](struct.Filter.html) with per-target directives.
+///
+/// # Panics
+///
+/// Panics if a different logger has already been set. See
+/// [`try_log_to()`](fn.try_log_to.html) for a non-panicking version.
+///
 /// # Examples
 ///
 /// ```rust
@@ -217,31 +953,154 @@ pub fn log_to_stderr(max_log_level: LevelFilter) {
 /// simple_logging::log_to(io::sink(), LevelFilter::Info);
 /// # }
 /// ```
-pub fn log_to<T: Write + Send + 'static>(sink: T, max_log_level: LevelFilter) {
-    LOGGER.renew(sink);
-    log::set_max_level(max_log_level);
-    // The only possible error is if this has been called before
-    let _ = log::set_logger(&*LOGGER);
-    // TODO: too much?
-    assert_eq!(log::logger() as *const Log, &*LOGGER as *const Log);
+///
+/// With per-target directives:
+///
+/// ```rust
+/// # extern crate simple_logging;
+/// use simple_logging::Filter;
+/// use std::io;
+///
+/// # fn main() {
+/// let filter: Filter = "hyper=warn,my_app::db=trace,info".parse().unwrap();
+/// simple_logging::log_to(io::sink(), filter);
+/// # }
+/// ```
+pub fn log_to<T: Write + Send + 'static, F: Into<Filter>>(sink: T, filter: F) {
+    install(vec![Output::new(sink)], filter.into());
+}
+
+/// Like [`log_to()`](fn.log_to.html), but returns an error instead of
+/// panicking if a different logger has already been set.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use std::io;
+///
+/// # fn main() {
+/// let result = simple_logging::try_log_to(io::sink(), LevelFilter::Info);
+/// if let Err(err) = result {
+///     eprintln!("logging disabled: {}", err);
+/// }
+/// # }
+/// ```
+pub fn try_log_to<T: Write + Send + 'static, F: Into<Filter>>(
+    sink: T,
+    filter: F,
+) -> Result<(), Error> {
+    try_install(vec![Output::new(sink)], filter.into())
+}
+
+/// Configure the [`log`](https://crates.io/crates/log) facade to log to
+/// several sinks at once, each with its own filter and format.
+///
+/// See [`Output`](struct.Output.html) for details.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::Output;
+/// use std::fs::File;
+/// use std::io;
+///
+/// # fn main() {
+/// let file = File::create("test.log").unwrap();
+/// simple_logging::log_to_outputs(vec![
+///     Output::new(file).filter(LevelFilter::Debug),
+///     Output::new(io::stderr()).filter(LevelFilter::Warn),
+/// ]);
+/// # }
+/// ```
+pub fn log_to_outputs<I: IntoIterator<Item = Output>>(outputs: I) {
+    install(
+        outputs.into_iter().collect(),
+        Filter::new(LevelFilter::Trace),
+    );
+}
+
+fn install(outputs: Vec<Output>, filter: Filter) {
+    if let Err(err) = try_install(outputs, filter) {
+        panic!("{}", err);
+    }
+}
+
+// Open a log file for writing without truncating it yet. The logger is only
+// registered once the file could be opened, and the file only truncated once
+// the logger is registered, so neither is changed if the other fails.
+fn open_unchanged<T: AsRef<Path>>(path: T) -> io::Result<File> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+}
+
+// Like `register()`, but panics if a different logger has already been set.
+fn expect_register() {
+    if let Err(err) = register() {
+        panic!("{}", err);
+    }
+}
+
+fn try_install(outputs: Vec<Output>, filter: Filter) -> Result<(), Error> {
+    register()?;
+    log::set_max_level(LOGGER.renew(outputs, filter));
+
+    Ok(())
+}
+
+// Set `LOGGER` as the logger of the `log` facade.
+fn register() -> Result<(), Error> {
+    // The only possible error is if a logger has been set before, which is
+    // fine if it is this one
+    if log::set_logger(&*LOGGER).is_err()
+        && log::logger() as *const dyn Log as *const u8
+            != &*LOGGER as *const dyn Log as *const u8
+    {
+        return Err(Error::LoggerAlreadySet);
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use log_to;
+    use OverflowPolicy;
+    use {failed_writes, set_error_policy, set_flush_policy, set_format};
+    use {log_to, log_to_file_append, log_to_outputs, log_to_with_guard};
+    use {set_filter, set_level, set_sink, try_log_to, try_log_to_file};
+    use {
+        AsyncSink, Builder, Capture, ErrorPolicy, Filter, FlushPolicy, Format,
+    };
+    use {Error, Output, Precision, Timestamp};
+
+    use log;
+    use log::{Level, Log, Record};
 
-    use log::LevelFilter::Info;
+    use log::LevelFilter::{Debug, Info, Off, Trace, Warn};
     use regex::Regex;
+    use std::env;
+    use std::fs;
     use std::io;
     use std::io::Write;
-    use std::str;
-    use std::sync::{Arc, Mutex};
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
 
-    struct VecProxy(Arc<Mutex<Vec<u8>>>);
+    struct FailingSink;
 
-    impl Write for VecProxy {
-        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-            self.0.lock().unwrap().write(buf)
+    impl Write for FailingSink {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("failed"))
         }
 
         fn flush(&mut self) -> io::Result<()> {
@@ -249,19 +1108,93 @@ mod tests {
         }
     }
 
+    #[test]
+    fn instance() {
+        let capture = Capture::new();
+        let logger = Builder::new()
+            .sink(capture.clone())
+            .filter(Filter::new(Info).directive("noisy", Off))
+            .format(Format::logfmt())
+            .flush_policy(FlushPolicy::AtLevel(Level::Error))
+            .build();
+        assert_eq!(logger.max_level(), Info);
+
+        for &(level, target) in &[
+            (Level::Info, "app"),
+            (Level::Debug, "app"),
+            (Level::Warn, "noisy"),
+        ] {
+            logger.log(
+                &Record::builder()
+                    .level(level)
+                    .target(target)
+                    .args(format_args!("test"))
+                    .build(),
+            );
+        }
+        assert!(capture.text().is_empty());
+        drop(logger);
+        let log = capture.text();
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.contains(" level=info "));
+        assert!(log.ends_with(" target=app msg=test\n"));
+    }
+
+    #[test]
+    fn interval() {
+        let capture = Capture::new();
+        let logger = Builder::new()
+            .sink(capture.clone())
+            .flush_policy(FlushPolicy::Interval(Duration::from_millis(100)))
+            .build();
+
+        logger.log(
+            &Record::builder()
+                .level(Level::Info)
+                .args(format_args!("buffered"))
+                .build(),
+        );
+        assert!(capture.text().is_empty());
+        // Flushed by the timer without logging anything else
+        for _ in 0..100 {
+            if !capture.text().is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        capture.assert_logged(Level::Info, "buffered");
+    }
+
+    #[test]
+    fn zero_interval() {
+        let capture = Capture::new();
+        let logger = Builder::new()
+            .sink(capture.clone())
+            .flush_policy(FlushPolicy::Interval(Duration::from_secs(0)))
+            .build();
+        assert!(logger.timer.lock().unwrap().is_none());
+
+        logger.log(
+            &Record::builder()
+                .level(Level::Info)
+                .args(format_args!("flushed"))
+                .build(),
+        );
+        capture.assert_logged(Level::Info, "flushed");
+    }
+
     // The `log` API forbids calling `set_logger()` more than once in the
     // lifetime of a single program (even after a `shutdown_logger()`), so
     // we stash all tests in a single function.
     // TODO: increase coverage by making `log_to*()` tests integration tests.
     #[test]
     fn test() {
-        let buf = Arc::new(Mutex::new(Vec::new()));
-        let proxy = VecProxy(buf.clone());
-        log_to(proxy, Info);
+        let capture = Capture::new();
+        log_to(capture.clone(), Info);
 
         // Test filtering
         debug!("filtered");
-        assert!(buf.lock().unwrap().is_empty());
+        assert!(capture.records().is_empty());
 
         // Test message format
         let pat = Regex::new(
@@ -269,7 +1202,186 @@ mod tests {
         )
         .unwrap();
         info!("test");
-        let line = str::from_utf8(&buf.lock().unwrap()).unwrap().to_owned();
-        assert!(pat.is_match(&line));
+        assert!(pat.is_match(&capture.text()));
+
+        // Test per-target filtering
+        capture.clear();
+        log_to(capture.clone(), Filter::new(Warn).directive("noisy", Trace));
+        info!("filtered");
+        trace!(target: "noisy::module", "noisy");
+        capture.assert_logged(Level::Trace, "noisy");
+        assert_eq!(capture.records().len(), 1);
+
+        // Test appending with session markers
+        let path = env::temp_dir()
+            .join(format!("simple-logging-append-{}.log", process::id()));
+        let _ = fs::remove_file(&path);
+        for message in &["first", "second"] {
+            log_to_file_append(&path, Warn, true).unwrap();
+            warn!("{}", message);
+        }
+        let log = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = log.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains(" INFO   --- session started (pid "));
+        assert!(lines[1].ends_with(" WARN   first"));
+        assert!(lines[2].contains(" INFO   --- session started (pid "));
+        assert!(lines[3].ends_with(" WARN   second"));
+        fs::remove_file(&path).unwrap();
+
+        // Test wall-clock timestamps
+        capture.clear();
+        set_format(Format::new().timestamp(Timestamp::Utc(Precision::Millis)));
+        log_to(capture.clone(), Info);
+        let pat = Regex::new(concat!(
+            r"^\[\d{4}-\d\d-\d\dT\d\d:\d\d:\d\d\.\d{3}Z] ",
+            r"\([0-9a-zA-Z]+\) INFO   test\n$"
+        ))
+        .unwrap();
+        info!("test");
+        assert!(pat.is_match(&capture.text()));
+        set_format(Format::new());
+
+        // Test buffering and flushing
+        capture.clear();
+        set_flush_policy(FlushPolicy::AtLevel(Level::Warn));
+        info!("buffered");
+        assert!(capture.records().is_empty());
+        log::logger().flush();
+        capture.assert_logged(Level::Info, "buffered");
+        set_flush_policy(FlushPolicy::Unbuffered);
+
+        // Test the flush guard with an asynchronous sink
+        capture.clear();
+        let sink = AsyncSink::new(capture.clone(), 16, OverflowPolicy::Block);
+        let guard = log_to_with_guard(sink, Info);
+        for i in 0..10 {
+            info!("{}", i);
+        }
+        drop(guard);
+        assert_eq!(capture.records().len(), 10);
+        info!("discarded");
+        assert_eq!(capture.records().len(), 10);
+
+        // Test error reporting and fallback sinks
+        capture.clear();
+        let reported = Arc::new(AtomicUsize::new(0));
+        let counter = reported.clone();
+        set_error_policy(
+            ErrorPolicy::new()
+                .on_error(move |_| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                })
+                .fallback(2, capture.clone()),
+        );
+        log_to(FailingSink, Info);
+        let failed = failed_writes();
+        info!("lost");
+        assert!(capture.records().is_empty());
+        info!("recovered");
+        info!("fallback");
+        assert_eq!(capture.records().len(), 2);
+        capture.assert_not_logged(Level::Info, "lost");
+        capture.assert_logged(Level::Info, "recovered");
+        capture.assert_logged(Level::Info, "fallback");
+        assert_eq!(failed_writes() - failed, 2);
+        assert_eq!(reported.load(Ordering::SeqCst), 2);
+
+        // Test logging from the error callback
+        set_error_policy(
+            ErrorPolicy::new().on_error(|err| error!("write failed: {}", err)),
+        );
+        log_to_outputs(vec![
+            Output::new(FailingSink),
+            Output::new(capture.clone()),
+        ]);
+        capture.clear();
+        let failed = failed_writes();
+        info!("lost");
+        assert_eq!(capture.records().len(), 2);
+        capture.assert_logged(Level::Info, "lost");
+        capture.assert_logged(Level::Error, "write failed: failed");
+        assert_eq!(failed_writes() - failed, 2);
+        set_error_policy(ErrorPolicy::new());
+
+        // Test logging to several outputs
+        let all = Capture::new();
+        let audit = Capture::new();
+        log_to_outputs(vec![
+            Output::new(all.clone()).filter(Debug),
+            Output::new(capture.clone()).filter(Warn),
+            Output::new(audit.clone())
+                .filter(Filter::new(Off).directive("audit", Info))
+                .format(Format::logfmt()),
+        ]);
+        capture.clear();
+        trace!("filtered");
+        debug!("debug");
+        warn!("warning");
+        info!(target: "audit", "login");
+        assert_eq!(all.records().len(), 3);
+        assert_eq!(capture.records().len(), 1);
+        capture.assert_logged(Level::Warn, "warning");
+        let log = audit.text();
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.ends_with(" target=audit msg=login\n"));
+
+        // Test reconfiguration without losing buffered messages
+        capture.clear();
+        log_to(capture.clone(), Filter::new(Info).directive("quiet", Off));
+        set_flush_policy(FlushPolicy::AtLevel(Level::Error));
+        info!("buffered");
+        let other = Capture::new();
+        set_sink(other.clone());
+        capture.assert_logged(Level::Info, "buffered");
+        set_flush_policy(FlushPolicy::Unbuffered);
+        set_level(Debug);
+        debug!("debug");
+        debug!(target: "quiet", "filtered");
+        set_filter(Warn);
+        info!("filtered");
+        assert_eq!(other.records().len(), 1);
+        other.assert_logged(Level::Debug, "debug");
+
+        // Test the fallible functions
+        capture.clear();
+        try_log_to(capture.clone(), Info).unwrap();
+        info!("test");
+        capture.assert_logged(Level::Info, "test");
+        let path = env::temp_dir().join("simple-logging-missing/test.log");
+        match try_log_to_file(path, Info) {
+            Err(Error::Io(_)) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // Test configuring everything at once
+        capture.clear();
+        Builder::new()
+            .sink(capture.clone())
+            .level(Debug)
+            .format(Format::logfmt())
+            .flush_policy(FlushPolicy::AtLevel(Level::Warn))
+            .init()
+            .unwrap();
+        debug!("buffered");
+        assert!(capture.text().is_empty());
+        trace!("filtered");
+        warn!("flushed");
+        let log = capture.text();
+        assert_eq!(log.lines().count(), 2);
+        assert!(log.contains(" level=debug "));
+        assert!(log.ends_with(" msg=flushed\n"));
+
+        // Test the flush guard with a builder
+        capture.clear();
+        let guard = Builder::new()
+            .sink(AsyncSink::new(capture.clone(), 16, OverflowPolicy::Block))
+            .flush_policy(FlushPolicy::AtLevel(Level::Error))
+            .init_with_guard()
+            .unwrap();
+        info!("queued");
+        drop(guard);
+        capture.assert_logged(Level::Info, "queued");
+        Builder::new().sink(io::sink()).init().unwrap();
     }
 }
@@ -0,0 +1,254 @@
+use color::{self, ColorChoice};
+use error_policy::{Errors, Failure};
+use filter::Filter;
+use flush::{Buffer, FlushPolicy};
+use format::Format;
+
+use log::{Level, LevelFilter, Metadata, Record};
+use std::fmt;
+use std::io;
+use std::io::{IsTerminal, Write};
+
+/// A sink with its own filter and format, for logging to several sinks at once
+/// with [`log_to_outputs()`](fn.log_to_outputs.html).
+///
+/// By default an output receives every message and uses the format set with
+/// [`set_format()`](fn.set_format.html). Each output is buffered separately
+/// according to the flush policy. Outputs created with
+/// [`Output::stdout()`](#method.stdout) and
+/// [`Output::stderr()`](#method.stderr) are colored when writing to a
+/// terminal, as described in [`ColorChoice`](enum.ColorChoice.html).
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::{Filter, Format, Output};
+/// use std::fs::File;
+/// use std::io;
+///
+/// # fn main() {
+/// let file = File::create("test.log").unwrap();
+/// let audit_file = File::create("test-audit.log").unwrap();
+/// let audit =
+///     Filter::new(LevelFilter::Off).directive("audit", LevelFilter::Info);
+/// simple_logging::log_to_outputs(vec![
+///     Output::new(file).filter(LevelFilter::Debug),
+///     Output::new(io::stderr()).filter(LevelFilter::Warn),
+///     Output::new(audit_file).filter(audit).format(Format::json()),
+/// ]);
+/// # }
+/// ```
+pub struct Output {
+    sink: Box<dyn Write + Send>,
+    filter: Option<Filter>,
+    // Messages at this level or more severe are skipped
+    below: Option<Level>,
+    format: Option<Format>,
+    color: ColorChoice,
+    // Whether the sink is a terminal, or `None` if it isn't `stdout` or
+    // `stderr`
+    terminal: Option<bool>,
+    // `color` resolved when the output is installed
+    colored: bool,
+    pending: Buffer,
+    // Consecutive failed writes
+    failures: usize,
+}
+
+impl Output {
+    /// Create an output writing every message to `sink`.
+    pub fn new<T: Write + Send + 'static>(sink: T) -> Output {
+        Output {
+            sink: Box::new(sink),
+            filter: None,
+            below: None,
+            format: None,
+            color: ColorChoice::Auto,
+            terminal: None,
+            colored: false,
+            pending: Buffer::new(),
+            failures: 0,
+        }
+    }
+
+    /// Create an output writing every message to `stdout`.
+    pub fn stdout() -> Output {
+        let stdout = io::stdout();
+        let terminal = stdout.is_terminal();
+        Output {
+            terminal: Some(terminal),
+            ..Output::new(stdout)
+        }
+    }
+
+    /// Create an output writing every message to `stderr`.
+    pub fn stderr() -> Output {
+        let stderr = io::stderr();
+        let terminal = stderr.is_terminal();
+        Output {
+            terminal: Some(terminal),
+            ..Output::new(stderr)
+        }
+    }
+
+    /// Set whether to color log lines written to this output.
+    pub fn color(mut self, color: ColorChoice) -> Output {
+        self.color = color;
+
+        self
+    }
+
+    /// Only write messages allowed by `filter` to this output. Messages must
+    /// also be allowed by the logger's own filter, if any.
+    pub fn filter<F: Into<Filter>>(mut self, filter: F) -> Output {
+        self.filter = Some(filter.into());
+
+        self
+    }
+
+    /// Only write messages less severe than `level` to this output, such as
+    /// `Info` and below for `Level::Warn`. Combined with a second output for
+    /// `level` and above, this splits messages between two sinks.
+    pub fn less_severe_than(mut self, level: Level) -> Output {
+        self.below = Some(level);
+
+        self
+    }
+
+    /// Use `format` for this output instead of the logger's format.
+    pub fn format(mut self, format: Format) -> Output {
+        self.format = Some(format);
+
+        self
+    }
+
+    pub(crate) fn max_level(&self) -> LevelFilter {
+        match self.filter {
+            Some(ref filter) => filter.max_level(),
+            None => LevelFilter::Trace,
+        }
+    }
+
+    pub(crate) fn enabled(&self, metadata: &Metadata) -> bool {
+        if self.skips(metadata.level()) {
+            return false;
+        }
+        match self.filter {
+            Some(ref filter) => filter.enabled(metadata),
+            None => true,
+        }
+    }
+
+    pub(crate) fn matches(&self, record: &Record) -> bool {
+        if self.skips(record.level()) {
+            return false;
+        }
+        match self.filter {
+            Some(ref filter) => filter.matches(record),
+            None => true,
+        }
+    }
+
+    // Whether messages at `level` are skipped regardless of the filter.
+    fn skips(&self, level: Level) -> bool {
+        match self.below {
+            Some(below) => level <= below,
+            None => false,
+        }
+    }
+
+    // The format of this output, or `default` if it has none of its own.
+    pub(crate) fn format_or<'a>(&'a self, default: &'a Format) -> &'a Format {
+        self.format.as_ref().unwrap_or(default)
+    }
+
+    // Decide whether to color log lines, checking the environment.
+    pub(crate) fn resolve_color(&mut self) {
+        self.colored = color::use_color(self.color, self.terminal);
+    }
+
+    pub(crate) fn colored(&self) -> bool {
+        self.colored
+    }
+
+    // Change the flush policy. Pending messages should be flushed first.
+    pub(crate) fn set_flush_policy(&mut self, policy: FlushPolicy) {
+        self.pending.set_policy(policy);
+    }
+
+    pub(crate) fn reset_failures(&mut self) {
+        self.failures = 0;
+    }
+
+    // Write a formatted message, switching to the fallback sink if needed.
+    pub(crate) fn write(
+        &mut self,
+        errors: &mut Errors,
+        message: &[u8],
+        level: Level,
+    ) -> Option<Failure> {
+        let result = self.pending.write(&mut self.sink, message, level);
+        let failure = errors.check(result, &mut self.failures, &mut self.sink);
+        if let Some(Failure { switched: true, .. }) = failure {
+            // Retry with the fallback sink
+            let result = self.pending.write(&mut self.sink, message, level);
+            errors.check(result, &mut self.failures, &mut self.sink);
+        }
+
+        failure
+    }
+
+    // Flush the output if there are buffered messages.
+    pub(crate) fn flush_pending(
+        &mut self,
+        errors: &mut Errors,
+    ) -> Option<Failure> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        self.flush(errors)
+    }
+
+    pub(crate) fn flush(&mut self, errors: &mut Errors) -> Option<Failure> {
+        let result = self.pending.flush(&mut self.sink);
+        errors.check(result, &mut self.failures, &mut self.sink)
+    }
+}
+
+impl fmt::Debug for Output {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Output")
+            .field("filter", &self.filter)
+            .field("below", &self.below)
+            .field("format", &self.format)
+            .field("color", &self.color)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Output;
+
+    use log::LevelFilter::Info;
+    use log::{Level, Record};
+    use std::io;
+
+    #[test]
+    fn less_severe_than() {
+        let output = Output::new(io::sink())
+            .filter(Info)
+            .less_severe_than(Level::Warn);
+        let matches =
+            |level| output.matches(&Record::builder().level(level).build());
+
+        assert!(!matches(Level::Error));
+        assert!(!matches(Level::Warn));
+        assert!(matches(Level::Info));
+        assert!(!matches(Level::Debug));
+    }
+}
@@ -0,0 +1,437 @@
+use time::{local_offset, unix_time, DateTime};
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// A file sink that rotates when it grows past a size threshold.
+///
+/// Log messages are appended to the file at `path`. Once writing a message
+/// would grow the file past `max_bytes`, the file is renamed to `<path>.1`,
+/// any previous `<path>.1` is renamed to `<path>.2` and so on, up to
+/// `<path>.<max_files>`. Older files are deleted. A new, empty file is then
+/// created at `path`.
+///
+/// Rotation only happens between writes, and the logger writes each message
+/// with a single call, so messages are never split between files. A message
+/// larger than `max_bytes` is still written whole to a fresh file. If rotating
+/// fails, the write that triggered it returns the error, and messages keep
+/// being appended to `path` until another `max_bytes` have been written.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::RotatingFile;
+///
+/// # fn main() {
+/// // Keep up to 5 rotated files of 10MiB each
+/// let file = RotatingFile::new("test.log", 10 * 1024 * 1024, 5).unwrap();
+/// simple_logging::log_to(file, LevelFilter::Info);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    /// Open the log file at `path`, creating it if it doesn't exist. Messages
+    /// are appended to existing files, and their current size counts towards
+    /// `max_bytes`.
+    pub fn new<T: AsRef<Path>>(
+        path: T,
+        max_bytes: u64,
+        max_files: usize,
+    ) -> io::Result<RotatingFile> {
+        let path = path.as_ref().to_owned();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+
+        Ok(RotatingFile {
+            path,
+            max_bytes,
+            max_files,
+            file,
+            written,
+        })
+    }
+
+    // The path of the `index`th rotated file.
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = OsString::from(&self.path);
+        path.push(format!(".{}", index));
+
+        path.into()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let result = self.shift();
+
+        // Even if shifting failed halfway through, keep logging to `path`,
+        // and only try again once another `max_bytes` have been written. If
+        // `path` can't be reopened either, keep logging to the old file rather
+        // than shifting the rotated files again on every write.
+        self.written = 0;
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        result
+    }
+
+    // Move the file at `path` to `<path>.1`, shifting the rotated files.
+    fn shift(&self) -> io::Result<()> {
+        if self.max_files == 0 {
+            return ignore_not_found(fs::remove_file(&self.path));
+        }
+
+        ignore_not_found(fs::remove_file(self.rotated_path(self.max_files)))?;
+        for index in (1..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(from, self.rotated_path(index + 1))?;
+            }
+        }
+        ignore_not_found(fs::rename(&self.path, self.rotated_path(1)))
+    }
+}
+
+fn ignore_not_found(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes
+        {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// How often a [`TimedRotatingFile`](struct.TimedRotatingFile.html) starts a
+/// new file.
+///
+/// Boundaries are in UTC, or in local time for files created with
+/// [`TimedRotatingFile::local()`](struct.TimedRotatingFile.html#method.local).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    /// At the start of every hour.
+    Hourly,
+    /// At midnight.
+    Daily,
+    /// At every multiple of the given interval since the Unix epoch. Intervals
+    /// are rounded down to whole seconds, with a minimum of one second.
+    Every(Duration),
+}
+
+impl Period {
+    fn seconds(self) -> i64 {
+        match self {
+            Period::Hourly => 3600,
+            Period::Daily => 86_400,
+            Period::Every(interval) => interval.as_secs().max(1) as i64,
+        }
+    }
+}
+
+/// A file sink that starts a new file at wall-clock boundaries.
+///
+/// The file name is obtained by expanding a pattern with the start time of
+/// the current period, in UTC by default or in local time if created with
+/// [`local()`](#method.local). The pattern supports `%Y` (year), `%m`
+/// (month), `%d` (day), `%H` (hour), `%M` (minute), `%S` (second) and `%%`
+/// (a literal `%`), so `app-%Y-%m-%d.log` gives one file per day when used
+/// with [`Period::Daily`](enum.Period.html#variant.Daily). Messages are
+/// appended if the file already exists.
+///
+/// Files are only switched between writes, and the logger writes each message
+/// with a single call, so messages are never split between files.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::{Period, TimedRotatingFile};
+///
+/// # fn main() {
+/// let file = TimedRotatingFile::new("test-%Y-%m-%d.log", Period::Daily).unwrap();
+/// simple_logging::log_to(file, LevelFilter::Info);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TimedRotatingFile {
+    pattern: String,
+    period: Period,
+    // Whether periods follow local time instead of UTC
+    local: bool,
+    file: File,
+    // Unix time at which the current file should be replaced
+    next: i64,
+}
+
+impl TimedRotatingFile {
+    /// Open the log file for the current period, creating it if it doesn't
+    /// exist. Periods start at UTC boundaries.
+    pub fn new<T: Into<String>>(
+        pattern: T,
+        period: Period,
+    ) -> io::Result<TimedRotatingFile> {
+        TimedRotatingFile::new_at(
+            pattern.into(),
+            period,
+            false,
+            SystemTime::now(),
+        )
+    }
+
+    /// Like [`new()`](#method.new), but periods start at local wall-clock
+    /// boundaries and file names use local time. Falls back to UTC on
+    /// platforms where the local offset can't be determined.
+    pub fn local<T: Into<String>>(
+        pattern: T,
+        period: Period,
+    ) -> io::Result<TimedRotatingFile> {
+        TimedRotatingFile::new_at(
+            pattern.into(),
+            period,
+            true,
+            SystemTime::now(),
+        )
+    }
+
+    fn new_at(
+        pattern: String,
+        period: Period,
+        local: bool,
+        now: SystemTime,
+    ) -> io::Result<TimedRotatingFile> {
+        let (file, next) =
+            TimedRotatingFile::open(&pattern, period, local, now)?;
+
+        Ok(TimedRotatingFile {
+            pattern,
+            period,
+            local,
+            file,
+            next,
+        })
+    }
+
+    // Open the file for the period containing `now`, returning it along with
+    // the end of the period.
+    fn open(
+        pattern: &str,
+        period: Period,
+        local: bool,
+        now: SystemTime,
+    ) -> io::Result<(File, i64)> {
+        let (start, end) = if local {
+            bounds(period, unix_time(now).0, local_offset)
+        } else {
+            bounds(period, unix_time(now).0, |_| 0)
+        };
+        let path = DateTime::from_unix(start, 0).format_pattern(pattern);
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok((file, end))
+    }
+
+    fn write_at(&mut self, buf: &[u8], now: SystemTime) -> io::Result<usize> {
+        if unix_time(now).0 >= self.next {
+            self.file.flush()?;
+            let (file, next) = TimedRotatingFile::open(
+                &self.pattern,
+                self.period,
+                self.local,
+                now,
+            )?;
+            self.file = file;
+            self.next = next;
+        }
+
+        self.file.write(buf)
+    }
+}
+
+// The period containing the Unix time `now`, given the offset from UTC of the
+// wall clock at any Unix time. Returns the start of the period in wall-clock
+// seconds since the epoch, and its end as Unix time.
+fn bounds<F: Fn(i64) -> i32>(
+    period: Period,
+    now: i64,
+    offset: F,
+) -> (i64, i64) {
+    let seconds = period.seconds();
+    let current = i64::from(offset(now));
+    let start = (now + current).div_euclid(seconds) * seconds;
+    let end = start + seconds;
+    // The offset may change by the end of the period, such as when daylight
+    // saving time starts or ends
+    let end = end - i64::from(offset(end - current));
+
+    (start, end)
+}
+
+impl Write for TimedRotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_at(buf, SystemTime::now())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bounds, Period, RotatingFile, TimedRotatingFile};
+    use time::DateTime;
+
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn rotate() {
+        let dir = env::temp_dir()
+            .join(format!("simple-logging-rotate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.log");
+
+        let mut file = RotatingFile::new(&path, 10, 2).unwrap();
+        for line in &["aaaa\n", "bbbb\n", "cccc\n", "dddd\n", "eeee\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("test.log"), "eeee\n");
+        assert_eq!(read("test.log.1"), "cccc\ndddd\n");
+        assert_eq!(read("test.log.2"), "aaaa\nbbbb\n");
+        assert!(!dir.join("test.log.3").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rotate_failure() {
+        let dir = env::temp_dir().join(format!(
+            "simple-logging-rotate-failure-{}",
+            std::process::id()
+        ));
+        // A directory can't be removed as if it was the oldest rotated file
+        fs::create_dir_all(dir.join("test.log.1/blocked")).unwrap();
+        let path = dir.join("test.log");
+
+        let mut file = RotatingFile::new(&path, 10, 1).unwrap();
+        file.write_all(b"aaaa\n").unwrap();
+        file.write_all(b"bbbb\n").unwrap();
+        assert!(file.write_all(b"cccc\n").is_err());
+        file.write_all(b"dddd\n").unwrap();
+        file.flush().unwrap();
+
+        let log = fs::read_to_string(&path).unwrap();
+        assert_eq!(log, "aaaa\nbbbb\ndddd\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reopen_failure() {
+        let dir = env::temp_dir().join(format!(
+            "simple-logging-reopen-failure-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.log");
+
+        let mut file = RotatingFile::new(&path, 10, 1).unwrap();
+        file.write_all(b"aaaa\n").unwrap();
+        file.write_all(b"bbbb\n").unwrap();
+        // Nothing is left to shift, but `path` can't be created again
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(file.write_all(b"cccc\n").is_err());
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("test.log.1"), "history\n").unwrap();
+        file.write_all(b"dddd\n").unwrap();
+        file.flush().unwrap();
+
+        let history = fs::read_to_string(dir.join("test.log.1")).unwrap();
+        assert_eq!(history, "history\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn timed() {
+        let dir = env::temp_dir()
+            .join(format!("simple-logging-timed-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let pattern = dir.join("test-%Y-%m-%d_%H.log");
+        let at = |seconds| UNIX_EPOCH + Duration::from_secs(seconds);
+
+        // 2000-02-29T23:30:00Z
+        let mut file = TimedRotatingFile::new_at(
+            pattern.to_str().unwrap().to_owned(),
+            Period::Daily,
+            false,
+            at(951_867_000),
+        )
+        .unwrap();
+        file.write_at(b"a\n", at(951_867_000)).unwrap();
+        file.write_at(b"b\n", at(951_868_799)).unwrap();
+        file.write_at(b"c\n", at(951_868_800)).unwrap();
+        file.flush().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("test-2000-02-29_00.log"), "a\nb\n");
+        assert_eq!(read("test-2000-03-01_00.log"), "c\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn local_bounds() {
+        // 2026-10-15T00:30:00Z, 09:30 in UTC+9
+        let now = 1_792_024_200;
+        let (start, end) = bounds(Period::Daily, now, |_| 9 * 3600);
+        let start = DateTime::from_unix(start, 0);
+        assert_eq!((start.year, start.month, start.day), (2026, 10, 15));
+        assert_eq!((start.hour, start.minute), (0, 0));
+        // 2026-10-15T15:00:00Z, midnight in UTC+9
+        assert_eq!(end, 1_792_076_400);
+
+        let (start, end) = bounds(Period::Daily, now, |_| 0);
+        assert_eq!(start, 1_792_022_400);
+        assert_eq!(end, start + 86_400);
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+use std::io::Write;
+
+// Written at the start of continuation lines in `Sanitize::Indent` mode.
+const CONTINUATION: &str = "\n    | ";
+
+/// How control characters in messages, targets, module paths, file names and
+/// thread names are written in the default format and templates. JSON and
+/// logfmt lines always escape them.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::{Format, Sanitize};
+///
+/// # fn main() {
+/// simple_logging::set_format(Format::new().sanitize(Sanitize::Escape));
+/// simple_logging::log_to_stderr(LevelFilter::Info);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Sanitize {
+    /// Write messages as-is, including any embedded newlines. This is the
+    /// default.
+    #[default]
+    Off,
+    /// Escape newlines, carriage returns and tabs as `\n`, `\r` and `\t`,
+    /// other control characters as `\u{..}` and backslashes as `\\`, so that
+    /// each message occupies exactly one line and escaped characters can't be
+    /// confused with literal ones.
+    Escape,
+    /// Start each continuation line of multi-line messages with an indented
+    /// `|` marker, and escape other control characters except tabs as in
+    /// `Escape`.
+    Indent,
+}
+
+// Sanitizes everything written through it according to the mode.
+pub(crate) struct Sanitizer<'a> {
+    pub out: &'a mut Vec<u8>,
+    pub mode: Sanitize,
+}
+
+impl<'a> fmt::Write for Sanitizer<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.mode == Sanitize::Off {
+            self.out.extend_from_slice(s.as_bytes());
+            return Ok(());
+        }
+
+        let mut start = 0;
+        for (index, c) in s.char_indices() {
+            let escaped = match (c, self.mode) {
+                ('\\', Sanitize::Escape) => true,
+                (c, _) => c.is_control(),
+            };
+            if !escaped {
+                continue;
+            }
+
+            self.out.extend_from_slice(&s.as_bytes()[start..index]);
+            start = index + c.len_utf8();
+            match (c, self.mode) {
+                ('\n', Sanitize::Indent) => {
+                    self.out.extend_from_slice(CONTINUATION.as_bytes())
+                }
+                ('\t', Sanitize::Indent) => self.out.push(b'\t'),
+                ('\n', _) => self.out.extend_from_slice(b"\\n"),
+                ('\r', _) => self.out.extend_from_slice(b"\\r"),
+                ('\t', _) => self.out.extend_from_slice(b"\\t"),
+                ('\\', _) => self.out.extend_from_slice(b"\\\\"),
+                _ => {
+                    let _ = write!(self.out, "\\u{{{:x}}}", c as u32);
+                }
+            }
+        }
+        self.out.extend_from_slice(&s.as_bytes()[start..]);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sanitize, Sanitizer};
+
+    use std::fmt::Write;
+
+    fn sanitize(mode: Sanitize, message: &str) -> String {
+        let mut out = Vec::new();
+        Sanitizer {
+            out: &mut out,
+            mode,
+        }
+        .write_str(message)
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn modes() {
+        let message = "a\nb\r\n\tc\x1b[31m\u{85}é\\n";
+
+        assert_eq!(sanitize(Sanitize::Off, message), message);
+        assert_eq!(
+            sanitize(Sanitize::Escape, message),
+            "a\\nb\\r\\n\\tc\\u{1b}[31m\\u{85}é\\\\n"
+        );
+        assert_eq!(
+            sanitize(Sanitize::Indent, message),
+            "a\n    | b\\r\n    | \tc\\u{1b}[31m\\u{85}é\\n"
+        );
+    }
+}
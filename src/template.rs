@@ -0,0 +1,233 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed log line template.
+///
+/// Templates are strings with placeholders between braces, which are replaced
+/// by the corresponding value of each log message. A newline is appended to
+/// every line. The following placeholders are supported:
+///
+/// - `{time}`: the timestamp, as configured with
+///   [`Format::timestamp()`](struct.Format.html#method.timestamp).
+/// - `{elapsed}`: time elapsed since the logger was configured, as
+///   `<hh>:<mm>:<ss>.<SSS>`.
+/// - `{wall}`: wall-clock time in RFC 3339 format. Uses the configured
+///   timestamp if it is a wall-clock one, and UTC with millisecond precision
+///   otherwise.
+/// - `{thread}`: the thread identifier, as configured with
+///   [`Format::thread()`](struct.Format.html#method.thread).
+/// - `{thread_id}`: the implementation-specific thread ID, in hex.
+/// - `{thread_name}`: the thread name, or the thread ID if it has none.
+/// - `{level}`: the log level.
+/// - `{target}`: the target of the message.
+/// - `{module}`: the module path where the message was logged.
+/// - `{file}`: the source file where the message was logged.
+/// - `{line}`: the source line where the message was logged.
+/// - `{message}`: the message itself.
+/// - `{kv}`: the structured key-value pairs of the message, each written as
+///   ` key=value`. Always empty unless the `kv` feature is enabled.
+///
+/// Missing values are written as `-`. A minimum width can be given after a
+/// colon, optionally preceded by `<` (align left, the default) or `>` (align
+/// right), as in `{level:5}` or `{line:>4}`. Literal braces are written as
+/// `{{` and `}}`.
+///
+/// Templates are parsed once, when the format is configured.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate log;
+/// # extern crate simple_logging;
+/// use log::LevelFilter;
+/// use simple_logging::{Format, Template};
+///
+/// # fn main() {
+/// let template: Template =
+///     "{time} [{level:5}] {target}: {message}".parse().unwrap();
+/// simple_logging::set_format(Format::new().template(template));
+/// simple_logging::log_to_stderr(LevelFilter::Info);
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Piece {
+    Literal(String),
+    Field(Field, Align, usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Field {
+    Time,
+    Elapsed,
+    Wall,
+    Thread,
+    ThreadId,
+    ThreadName,
+    Level,
+    Target,
+    Module,
+    File,
+    Line,
+    Message,
+    KeyValues,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Align {
+    Left,
+    Right,
+}
+
+impl Template {
+    pub(crate) fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+}
+
+impl FromStr for Template {
+    type Err = ParseTemplateError;
+
+    fn from_str(template: &str) -> Result<Template, ParseTemplateError> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest.find('}').ok_or_else(|| {
+                        ParseTemplateError::new("unclosed `{`")
+                    })?;
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(literal.split_off(0)));
+                    }
+                    pieces.push(parse_placeholder(&rest[..end])?);
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => return Err(ParseTemplateError::new("unmatched `}`")),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+
+        Ok(Template { pieces })
+    }
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<Piece, ParseTemplateError> {
+    let mut parts = placeholder.splitn(2, ':');
+    let name = parts.next().unwrap();
+    let field = match name {
+        "time" => Field::Time,
+        "elapsed" => Field::Elapsed,
+        "wall" => Field::Wall,
+        "thread" => Field::Thread,
+        "thread_id" => Field::ThreadId,
+        "thread_name" => Field::ThreadName,
+        "level" => Field::Level,
+        "target" => Field::Target,
+        "module" => Field::Module,
+        "file" => Field::File,
+        "line" => Field::Line,
+        "message" => Field::Message,
+        "kv" => Field::KeyValues,
+        _ => {
+            return Err(ParseTemplateError::new(&format!(
+                "unknown placeholder `{{{}}}`",
+                name
+            )))
+        }
+    };
+
+    let (align, width) = match parts.next() {
+        None => (Align::Left, 0),
+        Some(spec) => {
+            let (align, width) = if let Some(width) = spec.strip_prefix('>') {
+                (Align::Right, width)
+            } else if let Some(width) = spec.strip_prefix('<') {
+                (Align::Left, width)
+            } else {
+                (Align::Left, spec)
+            };
+            let width = width.parse().map_err(|_| {
+                ParseTemplateError::new(&format!(
+                    "invalid width in `{{{}}}`",
+                    placeholder
+                ))
+            })?;
+
+            (align, width)
+        }
+    };
+
+    Ok(Piece::Field(field, align, width))
+}
+
+/// The error returned when parsing a [`Template`](struct.Template.html)
+/// fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseTemplateError {
+    reason: String,
+}
+
+impl ParseTemplateError {
+    fn new(reason: &str) -> ParseTemplateError {
+        ParseTemplateError {
+            reason: reason.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ParseTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid log template: {}", self.reason)
+    }
+}
+
+impl Error for ParseTemplateError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Align, Field, Piece, Template};
+
+    #[test]
+    fn parse() {
+        let template: Template = "{{{time}}} [{level:5}] {line:>4}: {message}"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            template.pieces(),
+            &[
+                Piece::Literal("{".to_owned()),
+                Piece::Field(Field::Time, Align::Left, 0),
+                Piece::Literal("} [".to_owned()),
+                Piece::Field(Field::Level, Align::Left, 5),
+                Piece::Literal("] ".to_owned()),
+                Piece::Field(Field::Line, Align::Right, 4),
+                Piece::Literal(": ".to_owned()),
+                Piece::Field(Field::Message, Align::Left, 0),
+            ]
+        );
+        assert!("{time".parse::<Template>().is_err());
+        assert!("time}".parse::<Template>().is_err());
+        assert!("{nope}".parse::<Template>().is_err());
+        assert!("{level:x}".parse::<Template>().is_err());
+    }
+}
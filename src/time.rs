@@ -0,0 +1,271 @@
+use std::cell::Cell;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// A broken-down calendar date and time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub nanosecond: u32,
+    // Offset from UTC in seconds
+    pub offset: i32,
+}
+
+impl DateTime {
+    // The UTC date and time `seconds` seconds after the Unix epoch.
+    pub fn from_unix(seconds: i64, nanosecond: u32) -> DateTime {
+        let days = seconds.div_euclid(86_400);
+        let time = seconds.rem_euclid(86_400) as u32;
+        let (year, month, day) = civil_from_days(days);
+
+        DateTime {
+            year,
+            month,
+            day,
+            hour: time / 3600,
+            minute: (time / 60) % 60,
+            second: time % 60,
+            nanosecond,
+            offset: 0,
+        }
+    }
+
+    // The UTC date and time at `time`.
+    pub fn utc(time: SystemTime) -> DateTime {
+        let (seconds, nanosecond) = unix_time(time);
+
+        DateTime::from_unix(seconds, nanosecond)
+    }
+
+    // The local date and time at `time`. Falls back to UTC on platforms where
+    // the local offset can't be determined.
+    pub fn local(time: SystemTime) -> DateTime {
+        let (seconds, nanosecond) = unix_time(time);
+        let offset = local_offset(seconds);
+        let mut local =
+            DateTime::from_unix(seconds + i64::from(offset), nanosecond);
+        local.offset = offset;
+
+        local
+    }
+
+    // Write this date and time in RFC 3339 format with `digits` fractional
+    // digits (at most 9).
+    pub fn write_rfc3339<W: Write>(
+        &self,
+        out: &mut W,
+        digits: usize,
+    ) -> std::fmt::Result {
+        write!(
+            out,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second
+        )?;
+        if digits > 0 {
+            let digits = digits.min(9);
+            let fraction = self.nanosecond / 10u32.pow(9 - digits as u32);
+            write!(out, ".{:0width$}", fraction, width = digits)?;
+        }
+        if self.offset == 0 {
+            write!(out, "Z")
+        } else {
+            let sign = if self.offset < 0 { '-' } else { '+' };
+            let minutes = self.offset.abs() / 60;
+            write!(out, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+        }
+    }
+
+    // Expand a `strftime`-like pattern. Supports `%Y`, `%m`, `%d`, `%H`, `%M`,
+    // `%S` and `%%`. Any other sequence is copied as-is.
+    pub fn format_pattern(&self, pattern: &str) -> String {
+        let mut out = String::with_capacity(pattern.len() + 16);
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+
+            let _ = match chars.next() {
+                Some('Y') => write!(out, "{:04}", self.year),
+                Some('m') => write!(out, "{:02}", self.month),
+                Some('d') => write!(out, "{:02}", self.day),
+                Some('H') => write!(out, "{:02}", self.hour),
+                Some('M') => write!(out, "{:02}", self.minute),
+                Some('S') => write!(out, "{:02}", self.second),
+                Some('%') => write!(out, "%"),
+                Some(other) => write!(out, "%{}", other),
+                None => write!(out, "%"),
+            };
+        }
+
+        out
+    }
+}
+
+// Seconds and nanoseconds since the Unix epoch. Negative for times before it.
+pub fn unix_time(time: SystemTime) -> (i64, u32) {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+        Err(err) => {
+            let before = err.duration();
+            let seconds = -(before.as_secs() as i64);
+            match before.subsec_nanos() {
+                0 => (seconds, 0),
+                nanos => (seconds - 1, 1_000_000_000 - nanos),
+            }
+        }
+    }
+}
+
+// The local offset from UTC in seconds at `seconds` after the Unix epoch.
+// Looking it up is relatively expensive, so the result is reused for the rest
+// of the second.
+pub fn local_offset(seconds: i64) -> i32 {
+    thread_local! {
+        // The second whose offset was last looked up on this thread, and its
+        // offset
+        static CACHED: Cell<Option<(i64, i32)>> = const { Cell::new(None) };
+    }
+
+    CACHED.with(|cached| match cached.get() {
+        Some((at, offset)) if at == seconds => offset,
+        _ => {
+            let offset = system_offset(seconds);
+            cached.set(Some((seconds, offset)));
+            offset
+        }
+    })
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd"
+))]
+fn system_offset(seconds: i64) -> i32 {
+    use std::mem;
+
+    let time = seconds as libc::time_t;
+    // SAFETY: `tm` is a valid `libc::tm` for `localtime_r` to fill in, and
+    // `time` outlives the call. `localtime_r` also reads the `TZ` environment
+    // variable, which races with concurrent changes to the environment. As
+    // with `std::env::set_var`, this assumes the program doesn't modify the
+    // environment while other threads may be logging.
+    unsafe {
+        let mut tm: libc::tm = mem::zeroed();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            0
+        } else {
+            tm.tm_gmtoff as i32
+        }
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd"
+)))]
+fn system_offset(_: i64) -> i32 {
+    0
+}
+
+// Convert days since the Unix epoch into a (year, month, day) triple in the
+// proleptic Gregorian calendar. See
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DateTime;
+
+    #[test]
+    fn from_unix() {
+        let epoch = DateTime::from_unix(0, 0);
+        assert_eq!((epoch.year, epoch.month, epoch.day), (1970, 1, 1));
+
+        let leap = DateTime::from_unix(951_827_696, 5);
+        assert_eq!(
+            leap,
+            DateTime {
+                year: 2000,
+                month: 2,
+                day: 29,
+                hour: 12,
+                minute: 34,
+                second: 56,
+                nanosecond: 5,
+                offset: 0,
+            }
+        );
+
+        let before = DateTime::from_unix(-1, 0);
+        assert_eq!((before.year, before.month, before.day), (1969, 12, 31));
+        assert_eq!((before.hour, before.minute, before.second), (23, 59, 59));
+    }
+
+    #[test]
+    fn rfc3339() {
+        let rfc3339 = |time: DateTime, digits| {
+            let mut out = String::new();
+            time.write_rfc3339(&mut out, digits).unwrap();
+            out
+        };
+        let mut time = DateTime::from_unix(951_827_696, 123_456_789);
+
+        assert_eq!(rfc3339(time, 0), "2000-02-29T12:34:56Z");
+        assert_eq!(rfc3339(time, 3), "2000-02-29T12:34:56.123Z");
+        assert_eq!(rfc3339(time, 9), "2000-02-29T12:34:56.123456789Z");
+        time.offset = -(5 * 3600 + 30 * 60);
+        assert_eq!(rfc3339(time, 6), "2000-02-29T12:34:56.123456-05:30");
+    }
+
+    #[test]
+    fn format_pattern() {
+        let time = DateTime::from_unix(951_827_696, 0);
+        assert_eq!(
+            time.format_pattern("app-%Y-%m-%d_%H%M%S.log%%%q"),
+            "app-2000-02-29_123456.log%%q"
+        );
+    }
+}
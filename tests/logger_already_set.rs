@@ -0,0 +1,56 @@
+extern crate log;
+extern crate simple_logging;
+
+use log::LevelFilter::Info;
+use log::{Log, Metadata, Record};
+use simple_logging::Error;
+use std::env;
+use std::fs;
+use std::panic;
+use std::process;
+
+struct OtherLogger;
+
+impl Log for OtherLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        false
+    }
+
+    fn log(&self, _: &Record) {}
+
+    fn flush(&self) {}
+}
+
+static OTHER_LOGGER: OtherLogger = OtherLogger;
+
+// Each integration test is its own program, so a different logger can be set
+// here without affecting the unit tests.
+#[test]
+fn existing_file_is_kept() {
+    // Failing to open the file leaves the logger of the `log` facade unset
+    let missing = env::temp_dir().join("simple-logging-missing/test.log");
+    match simple_logging::try_log_to_file(&missing, Info) {
+        Err(Error::Io(_)) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+    assert!(simple_logging::log_to_file(&missing, Info).is_err());
+
+    log::set_logger(&OTHER_LOGGER).unwrap();
+    let path = env::temp_dir()
+        .join(format!("simple-logging-already-set-{}.log", process::id()));
+    fs::write(&path, "precious\n").unwrap();
+
+    match simple_logging::try_log_to_file(&path, Info) {
+        Err(Error::LoggerAlreadySet) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(fs::read_to_string(&path).unwrap(), "precious\n");
+
+    let result = panic::catch_unwind(|| {
+        let _ = simple_logging::log_to_file(&path, Info);
+    });
+    assert!(result.is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "precious\n");
+
+    fs::remove_file(&path).unwrap();
+}